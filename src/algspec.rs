@@ -0,0 +1,457 @@
+use crate::cube::{Amount, Axis, Face, Layer, Move, Slice};
+use crate::diagnostics::{ParseError, ParseResult};
+use std::fmt;
+
+/*
+    Grammar for move sequences in WCA/SiGN notation:
+
+    algorithm => nodes
+    nodes => node nodes
+          =>
+    node => atom suffix
+    atom => move
+         => '(' nodes ')'
+         => '[' nodes ',' nodes ']'       commutator: A B A' B'
+         => '[' nodes ':' nodes ']'       conjugate: A B A'
+    move => face ['w']                    R, Rw
+         => wide                          r
+         => slice                         M
+         => rotation                      x
+    face => [RUFDLB]
+    wide => [rufdlb]
+    slice => [MES]
+    rotation => [xyz]
+    suffix => [0-9]* [']
+*/
+
+/// The largest repetition count accepted, since `expand` writes out every repetition.
+const MAX_REPETITIONS: u32 = 100;
+
+/// The most moves an algorithm may expand to. Nested repeats and brackets multiply, so this
+/// is checked as well as the repetition count.
+const MAX_MOVES: usize = 100_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Algorithm {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Move(Move),
+    Group(Algorithm),
+    Commutator(Algorithm, Algorithm),
+    Conjugate(Algorithm, Algorithm),
+    Repeat(Box<Node>, u32),
+    Inverse(Box<Node>),
+}
+
+impl Algorithm {
+    /// Flatten the algorithm into the moves it performs.
+    pub fn expand(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for node in &self.nodes {
+            node.expand_into(&mut moves);
+        }
+        moves
+    }
+
+    /// The moves that undo this algorithm.
+    pub fn expand_inverse(&self) -> Vec<Move> {
+        invert(&self.expand())
+    }
+
+    fn move_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .fold(0, |total, node| total.saturating_add(node.move_count()))
+    }
+}
+
+impl Node {
+    /// How many moves `expand_into` would write out.
+    fn move_count(&self) -> usize {
+        match self {
+            Node::Move(_) => 1,
+            Node::Group(alg) => alg.move_count(),
+            Node::Commutator(a, b) => a
+                .move_count()
+                .saturating_add(b.move_count())
+                .saturating_mul(2),
+            Node::Conjugate(a, b) => a
+                .move_count()
+                .saturating_mul(2)
+                .saturating_add(b.move_count()),
+            Node::Repeat(node, count) => node.move_count().saturating_mul(*count as usize),
+            Node::Inverse(node) => node.move_count(),
+        }
+    }
+
+    fn expand_into(&self, moves: &mut Vec<Move>) {
+        match self {
+            Node::Move(mv) => moves.push(*mv),
+            Node::Group(alg) => moves.extend(alg.expand()),
+            Node::Commutator(a, b) => {
+                let a = a.expand();
+                let b = b.expand();
+                moves.extend(a.iter());
+                moves.extend(b.iter());
+                moves.extend(invert(&a));
+                moves.extend(invert(&b));
+            }
+            Node::Conjugate(a, b) => {
+                let a = a.expand();
+                moves.extend(a.iter());
+                moves.extend(b.expand());
+                moves.extend(invert(&a));
+            }
+            Node::Repeat(node, count) => {
+                let mut once = vec![];
+                node.expand_into(&mut once);
+                for _ in 0..*count {
+                    moves.extend(once.iter());
+                }
+            }
+            Node::Inverse(node) => {
+                let mut once = vec![];
+                node.expand_into(&mut once);
+                moves.extend(invert(&once));
+            }
+        }
+    }
+}
+
+fn invert(moves: &[Move]) -> Vec<Move> {
+    moves.iter().rev().map(|mv| mv.inverse()).collect()
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| node.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", nodes.join(" "))
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Move(mv) => write!(f, "{}", mv),
+            Node::Group(alg) => write!(f, "({})", alg),
+            Node::Commutator(a, b) => write!(f, "[{}, {}]", a, b),
+            Node::Conjugate(a, b) => write!(f, "[{}: {}]", a, b),
+            Node::Repeat(node, count) => write!(f, "{}{}", node, count),
+            Node::Inverse(node) => write!(f, "{}'", node),
+        }
+    }
+}
+
+pub fn parse_algorithm(input: &str) -> ParseResult<Algorithm> {
+    parse_complete_algorithm(input).map_err(|err| err.locate(input))
+}
+
+fn parse_complete_algorithm(input: &str) -> ParseResult<Algorithm> {
+    let (alg, tail) = parse_nodes(input)?;
+
+    if tail.is_empty() {
+        Ok(alg)
+    } else {
+        Err(ParseError::unexpected(tail, "Algorithm").expecting(["a move", "'('", "'['"]))
+    }
+}
+
+/// Parse nodes until the input runs out or a closing delimiter is found.
+fn parse_nodes(input: &str) -> ParseResult<(Algorithm, &str)> {
+    let mut nodes = vec![];
+    let mut moves = 0usize;
+
+    let mut tail = input.trim_start();
+    while let Some(ch) = tail.chars().next() {
+        if ch == ')' || ch == ']' || ch == ',' || ch == ':' {
+            break;
+        }
+        let (node, tail_) = parse_node(tail)?;
+        moves = moves.saturating_add(node.move_count());
+        if moves > MAX_MOVES {
+            return Err(ParseError::new(
+                tail,
+                format!(
+                    "Algorithm is too long, it makes more than {} moves",
+                    MAX_MOVES
+                ),
+            ));
+        }
+        nodes.push(node);
+        tail = tail_.trim_start();
+    }
+
+    Ok((Algorithm { nodes }, tail))
+}
+
+fn parse_node(input: &str) -> ParseResult<(Node, &str)> {
+    let (node, input) = match input.chars().next() {
+        Some('(') => parse_group(&input[1..])?,
+        Some('[') => parse_bracket(&input[1..])?,
+        _ => {
+            let (mv, input) = parse_move(input)?;
+            (Node::Move(mv), input)
+        }
+    };
+
+    parse_suffix(node, input)
+}
+
+fn parse_group(input: &str) -> ParseResult<(Node, &str)> {
+    let (alg, input) = parse_nodes(input)?;
+    let input = expect(input, ')', "Group")?;
+    Ok((Node::Group(alg), input))
+}
+
+fn parse_bracket(input: &str) -> ParseResult<(Node, &str)> {
+    let (a, input) = parse_nodes(input)?;
+    let (is_commutator, input) = match input.chars().next() {
+        Some(',') => (true, &input[1..]),
+        Some(':') => (false, &input[1..]),
+        _ => {
+            return Err(
+                ParseError::unexpected(input, "Commutator or Conjugate").expecting(["','", "':'"])
+            )
+        }
+    };
+    let (b, input) = parse_nodes(input)?;
+    let input = expect(input, ']', "Commutator or Conjugate")?;
+
+    if is_commutator {
+        Ok((Node::Commutator(a, b), input))
+    } else {
+        Ok((Node::Conjugate(a, b), input))
+    }
+}
+
+fn expect<'a>(input: &'a str, expected: char, parsing: &str) -> ParseResult<&'a str> {
+    match input.strip_prefix(expected) {
+        Some(tail) => Ok(tail),
+        None => Err(ParseError::unexpected(input, parsing).expecting([format!("'{}'", expected)])),
+    }
+}
+
+/// Repetition counts and primes apply to whatever came before them, so `(R U)2'` is the
+/// inverse of `(R U)2`. On a single move they are folded into its amount instead.
+fn parse_suffix(node: Node, input: &str) -> ParseResult<(Node, &str)> {
+    let (count, input) = parse_count(input)?;
+    let (prime, input) = parse_prime(input);
+
+    if let Node::Move(mv) = node {
+        let turns = count.unwrap_or(1) % 4 * if prime { 3 } else { 1 };
+        if let Some(amount) = Amount::from_quarter_turns(turns) {
+            return Ok((Node::Move(Move::new(mv.layer, amount)), input));
+        }
+    }
+
+    let node = match count {
+        Some(count) => Node::Repeat(Box::new(node), count),
+        None => node,
+    };
+    let node = if prime {
+        Node::Inverse(Box::new(node))
+    } else {
+        node
+    };
+
+    Ok((node, input))
+}
+
+fn parse_count(input: &str) -> ParseResult<(Option<u32>, &str)> {
+    let digits = input
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(input.len());
+    if digits == 0 {
+        return Ok((None, input));
+    }
+
+    let count = input[..digits]
+        .parse::<u32>()
+        .ok()
+        .filter(|count| *count <= MAX_REPETITIONS)
+        .ok_or_else(|| {
+            ParseError::new(
+                input,
+                format!(
+                    "Invalid repetition count, {} is more than {}",
+                    &input[..digits],
+                    MAX_REPETITIONS
+                ),
+            )
+        })?;
+    Ok((Some(count), &input[digits..]))
+}
+
+fn parse_prime(input: &str) -> (bool, &str) {
+    match input.chars().next() {
+        Some(ch @ '\'') | Some(ch @ '’') | Some(ch @ '′') => (true, &input[ch.len_utf8()..]),
+        _ => (false, input),
+    }
+}
+
+/// Parse a single move. The amount is handled by `parse_suffix`, so the move returned
+/// here is always a clockwise quarter turn.
+fn parse_move(input: &str) -> ParseResult<(Move, &str)> {
+    let unexpected = || ParseError::unexpected(input, "Move").expecting(["a move"]);
+    let ch = input.chars().next().ok_or_else(unexpected)?;
+    let tail = &input[ch.len_utf8()..];
+
+    let layer = match ch {
+        'M' => Layer::Slice(Slice::M),
+        'E' => Layer::Slice(Slice::E),
+        'S' => Layer::Slice(Slice::S),
+        'x' => Layer::Rotation(Axis::X),
+        'y' => Layer::Rotation(Axis::Y),
+        'z' => Layer::Rotation(Axis::Z),
+        _ => {
+            if let Some(face) = Face::from_letter(ch) {
+                Layer::Face(face)
+            } else if let Some(face) = Face::from_letter(ch.to_ascii_uppercase()) {
+                Layer::Wide(face)
+            } else {
+                return Err(unexpected());
+            }
+        }
+    };
+
+    let (layer, tail) = match (layer, tail.strip_prefix('w')) {
+        (Layer::Face(face), Some(tail)) => (Layer::Wide(face), tail),
+        _ => (layer, tail),
+    };
+
+    Ok((Move::new(layer, Amount::Clockwise), tail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: &str) -> String {
+        let alg = parse_algorithm(input).unwrap();
+        let moves = alg.expand();
+        moves
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn error(input: &str) -> (usize, String) {
+        let err = parse_algorithm(input).unwrap_err();
+        (err.offset(), err.message().to_string())
+    }
+
+    #[test]
+    fn moves_and_amounts() {
+        assert_eq!(expand("R U2 F' D3 L5 U2'"), "R U2 F' D' L U2");
+        assert_eq!(expand("B4'"), "B' B' B' B'");
+        assert_eq!(expand("R’ U′"), "R' U'");
+        assert_eq!(expand(""), "");
+    }
+
+    #[test]
+    fn wide_slice_and_rotation_moves() {
+        assert_eq!(expand("r Rw u2 Uw'"), "Rw Rw Uw2 Uw'");
+        assert_eq!(expand("M E2 S'"), "M E2 S'");
+        assert_eq!(expand("x y2 z'"), "x y2 z'");
+    }
+
+    #[test]
+    fn groups_and_repeats() {
+        assert_eq!(expand("(R U)2"), "R U R U");
+        assert_eq!(expand("(R U)'"), "U' R'");
+        assert_eq!(expand("(R U)2'"), "U' R' U' R'");
+        assert_eq!(expand("((R)2 U)2"), "R R U R R U");
+        assert_eq!(expand("(R U)0"), "");
+    }
+
+    #[test]
+    fn commutators_and_conjugates() {
+        assert_eq!(expand("[R, U]"), "R U R' U'");
+        assert_eq!(expand("[R U: F]"), "R U F U' R'");
+        assert_eq!(expand("[F: [R, U]]"), "F R U R' U' F'");
+        assert_eq!(expand("[R, U]2"), "R U R' U' R U R' U'");
+        assert_eq!(expand("[R, U]'"), "U R U' R'");
+    }
+
+    #[test]
+    fn expand_inverse() {
+        let alg = parse_algorithm("R U2 F'").unwrap();
+        let inverse = alg.expand_inverse();
+        assert_eq!(
+            inverse.iter().map(|mv| mv.to_string()).collect::<Vec<_>>(),
+            ["F", "U2", "R'"]
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let input = "R (U R')2' [F: [R, U]] x M2";
+        let alg = parse_algorithm(input).unwrap();
+        assert_eq!(alg.to_string(), input);
+        assert_eq!(parse_algorithm(&alg.to_string()).unwrap(), alg);
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(
+            error("R U Q"),
+            (4, "Unexpected 'Q' while parsing Move".to_string())
+        );
+        assert_eq!(
+            error("(R U"),
+            (4, "Unexpected end of input while parsing Group".to_string())
+        );
+        assert_eq!(
+            error("[R U]"),
+            (
+                4,
+                "Unexpected ']' while parsing Commutator or Conjugate".to_string()
+            )
+        );
+        assert_eq!(
+            error("[R, U"),
+            (
+                5,
+                "Unexpected end of input while parsing Commutator or Conjugate".to_string()
+            )
+        );
+        assert_eq!(
+            error("R U)"),
+            (3, "Unexpected ')' while parsing Algorithm".to_string())
+        );
+    }
+
+    #[test]
+    fn repetition_limits() {
+        assert_eq!(parse_algorithm("R100").unwrap().expand().len(), 100);
+        assert_eq!(
+            error("(R U R' U')4000000000"),
+            (
+                11,
+                "Invalid repetition count, 4000000000 is more than 100".to_string()
+            )
+        );
+        assert_eq!(
+            error("R U (R U)101"),
+            (
+                9,
+                "Invalid repetition count, 101 is more than 100".to_string()
+            )
+        );
+        assert_eq!(
+            error("R ((((R U)100)100)100)"),
+            (
+                3,
+                "Algorithm is too long, it makes more than 100000 moves".to_string()
+            )
+        );
+    }
+}
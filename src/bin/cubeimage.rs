@@ -0,0 +1,85 @@
+use cubetools::algspec::parse_algorithm;
+use cubetools::colors::ColorScheme;
+use cubetools::cube::Cube;
+use cubetools::isorender::{render_with_projection, Projection};
+use cubetools::netrender::render as net_render;
+use cubetools::{RenderOpts, Result};
+
+/// Draw a whole cube after an algorithm has been applied to it.
+#[derive(argh::FromArgs)]
+struct Args {
+    #[argh(positional, default = "String::new()")]
+    /// moves to apply to the cube, like a scramble
+    alg: String,
+
+    #[argh(switch, short = 'c')]
+    /// draw the case that the algorithm solves instead, by undoing it
+    case: bool,
+
+    #[argh(option, short = 's')]
+    /// start from this cube instead of a solved one: 54 facelets in URFDLB order, like
+    /// "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+    state: Option<String>,
+
+    #[argh(switch, short = 'n')]
+    /// draw the cube unfolded into a net instead of in 3D
+    net: bool,
+
+    #[argh(option, default = "25", short = 'w')]
+    /// width of each cubie
+    cubie_size: u32,
+
+    #[argh(option, default = "Projection::isometric().yaw")]
+    /// degrees to turn the cube about its vertical axis
+    yaw: f64,
+
+    #[argh(option, default = "Projection::isometric().pitch")]
+    /// degrees to tip the top of the cube towards the viewer
+    pitch: f64,
+
+    #[argh(option, default = "ColorScheme::default()")]
+    /// color scheme: "western", "japanese", "deuteranopia" or "protanopia"
+    colors: ColorScheme,
+
+    #[argh(switch)]
+    /// draw patterns on the stickers as well as coloring them
+    patterns: bool,
+}
+
+fn specs_from_args(args: &Args) -> RenderOpts {
+    RenderOpts {
+        colors: args.colors.clone(),
+        patterns: args.patterns,
+        ..RenderOpts::with_cubie_size(args.cubie_size)
+    }
+}
+
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+
+    let specs = specs_from_args(&args);
+    let alg = parse_algorithm(&args.alg)?;
+    let mut cube = match &args.state {
+        Some(state) => state.parse::<Cube>()?,
+        None => Cube::solved(),
+    };
+    if args.case {
+        cube.apply_all(&alg.expand_inverse());
+    } else {
+        cube.apply_all(&alg.expand());
+    }
+
+    let svg = if args.net {
+        net_render(&cube, &specs)
+    } else {
+        let projection = Projection {
+            yaw: args.yaw,
+            pitch: args.pitch,
+        };
+        render_with_projection(&cube, &projection, &specs)
+    };
+
+    println!("{}", svg);
+
+    Ok(())
+}
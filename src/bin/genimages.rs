@@ -1,11 +1,20 @@
 use anyhow::{anyhow, Context, Error};
 use argh::FromArgs;
-use cubetools::ollrender::render as oll_render;
-use cubetools::ollspec::{parse_desc, Direction};
-use cubetools::pllrender::render as pll_render;
-use cubetools::pllspec::{parse_program, Program};
+use cubetools::algspec::parse_algorithm;
+use cubetools::colors::ColorScheme;
+use cubetools::llrender::scene as ll_scene;
+use cubetools::llspec::{parse_last_layer, validate_last_layer, LastLayer};
+use cubetools::ollcases::{case_by_number, identify as identify_oll};
+use cubetools::ollrender::scene as oll_scene;
+use cubetools::ollspec::{desc_for_algorithm, parse_desc, validate_desc, Direction};
+use cubetools::pllcases::{case_by_name, identify as identify_pll};
+use cubetools::pllrender::scene as pll_scene;
+use cubetools::pllspec::{parse_program, program_for_algorithm, validate_program, Program};
+use cubetools::pngrender::{PngRenderer, SVG_DPI};
+use cubetools::scene::{Renderer, Scene};
+use cubetools::svgrender::SvgRenderer;
 use cubetools::RenderOpts;
-use once_cell::unsync::Lazy;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use std::fs::File;
 use std::io::Write;
@@ -26,6 +35,26 @@ struct Args {
     )]
     /// destination path for the images.
     dest_path: PathBuf,
+
+    #[argh(switch)]
+    /// say which OLL or PLL case each image shows, and fail if any does not show a known one.
+    check: bool,
+
+    #[argh(option, default = "ColorScheme::default()")]
+    /// color scheme: "western", "japanese", "deuteranopia" or "protanopia".
+    colors: ColorScheme,
+
+    #[argh(switch)]
+    /// draw patterns on the stickers as well as coloring them.
+    patterns: bool,
+
+    #[argh(switch)]
+    /// write PNG images instead of SVG.
+    png: bool,
+
+    #[argh(option, default = "SVG_DPI")]
+    /// resolution of PNG images in dots per inch; 96 makes one pixel per SVG pixel.
+    dpi: f64,
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -44,40 +73,75 @@ impl ImageDesc {
         })
     }
 
-    fn render(&self) -> Result<String> {
-        self.spec.render()
+    fn scene(&self, specs: &RenderOpts) -> Scene {
+        self.spec.scene(specs)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum CubeSpec {
-    OLL(Vec<Direction>),
-    PLL(Program),
+    Oll(Vec<Direction>),
+    Pll(Program),
+    LastLayer(LastLayer),
 }
 
 impl CubeSpec {
     fn new(spec_str: &str) -> Result<CubeSpec> {
-        if spec_str.contains('=') {
-            Ok(CubeSpec::OLL(parse_desc(spec_str)?))
-        } else if spec_str.contains('<') || spec_str.contains('>') {
-            Ok(CubeSpec::PLL(parse_program(spec_str)?))
+        if let Some(alg_str) = spec_str.strip_prefix("alg:") {
+            // An algorithm that leaves the U layer oriented can only be a PLL.
+            let alg = parse_algorithm(alg_str)?;
+            match program_for_algorithm(&alg) {
+                Ok(program) => Ok(CubeSpec::Pll(program)),
+                Err(_) => Ok(CubeSpec::Oll(desc_for_algorithm(&alg)?)),
+            }
+        } else if let Some(number) = spec_str.strip_prefix("oll:") {
+            let case = number
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .and_then(case_by_number)
+                .ok_or_else(|| anyhow!("'{}' is not an OLL case number", number.trim()))?;
+            Ok(CubeSpec::Oll(case.desc.clone()))
+        } else if let Some(name) = spec_str.strip_prefix("pll:") {
+            let case = case_by_name(name.trim())
+                .ok_or_else(|| anyhow!("'{}' is not a PLL case name", name.trim()))?;
+            Ok(CubeSpec::Pll(case.program.clone()))
+        } else if spec_str.contains('/') {
+            let last_layer = parse_last_layer(spec_str)?;
+            validate_last_layer(&last_layer)?;
+            Ok(CubeSpec::LastLayer(last_layer))
+        } else if spec_str.contains('=') {
+            let desc = parse_desc(spec_str)?;
+            validate_desc(&desc)?;
+            Ok(CubeSpec::Oll(desc))
+        } else if spec_str.contains('<')
+            || spec_str.contains('>')
+            || spec_str.trim_start().starts_with('(')
+        {
+            let program = parse_program(spec_str)?;
+            validate_program(&program)?;
+            Ok(CubeSpec::Pll(program))
         } else {
-            Err(anyhow!("'{}' is not a valid image spec"))
+            Err(anyhow!("'{}' is not a valid image spec", spec_str))
         }
     }
 
-    fn render(&self) -> Result<String> {
-        let specs = RenderOpts::with_cubie_size(25);
-        let svg = match self {
-            CubeSpec::OLL(oll_spec) => oll_render(oll_spec, &specs),
-            CubeSpec::PLL(pll_spec) => pll_render(pll_spec, &specs),
-        };
-        Ok(svg)
+    fn scene(&self, specs: &RenderOpts) -> Scene {
+        match self {
+            CubeSpec::Oll(oll_spec) => oll_scene(oll_spec, specs),
+            CubeSpec::Pll(pll_spec) => pll_scene(pll_spec, specs),
+            CubeSpec::LastLayer(ll_spec) => ll_scene(ll_spec, specs),
+        }
     }
 }
 
 // Example: '[//]: # (bar  xUx===xDx)'
-const IMAGE_DESC_RE: Lazy<Regex> =
+//          '[//]: # (sune  alg: R U R' U R U2 R')'
+//          '[//]: # (sune  oll:27)'
+//          '[//]: # (ua  pll:Ua)'
+//          '[//]: # (ua  4>8>6>4 {auto})'
+//          '[//]: # (sune  U=R === ==D / 1<>9 3<>7 2>4>6>2)'
+static IMAGE_DESC_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new("# *\\(([[:alnum:]]+)  (.*)\\)").unwrap());
 
 fn process_line(line: &str) -> Result<Option<ImageDesc>> {
@@ -90,8 +154,10 @@ fn process_line(line: &str) -> Result<Option<ImageDesc>> {
 
 fn process_input(reader: impl BufRead) -> Result<Vec<ImageDesc>> {
     let mut descs = vec![];
-    for line in reader.lines() {
-        if let Some(image_desc) = process_line(&line?)? {
+    for (line_number, line) in reader.lines().enumerate() {
+        let image_desc = process_line(&line?)
+            .with_context(|| format!("Bad image spec on line {}", line_number + 1))?;
+        if let Some(image_desc) = image_desc {
             descs.push(image_desc);
         }
     }
@@ -99,26 +165,105 @@ fn process_input(reader: impl BufRead) -> Result<Vec<ImageDesc>> {
     Ok(descs)
 }
 
-fn render_descs(descs: &[ImageDesc], dest_path: &Path) -> Result<()> {
+/// The format to write images in.
+enum Format {
+    Svg,
+    Png { dpi: f64 },
+}
+
+fn render_descs(
+    descs: &[ImageDesc],
+    dest_path: &Path,
+    specs: &RenderOpts,
+    format: &Format,
+) -> Result<()> {
     for desc in descs {
-        let full_path = dest_path.join(&desc.file_stem).with_extension("svg");
-        let svg = desc.render()?;
+        let scene = desc.scene(specs);
+        let (extension, bytes) = match format {
+            Format::Svg => {
+                let mut svg = SvgRenderer::new().render(&scene);
+                svg.push('\n');
+                ("svg", svg.into_bytes())
+            }
+            Format::Png { dpi } => ("png", PngRenderer::with_dpi(*dpi).render(&scene)?),
+        };
+        let full_path = dest_path.join(&desc.file_stem).with_extension(extension);
 
         let mut output =
             File::create(&full_path).context(format!("Cannot create '{:?}'", &full_path))?;
-        output.write_all(svg.as_bytes())?;
-        output.write_all("\n".as_bytes())?;
+        output.write_all(&bytes)?;
     }
 
     Ok(())
 }
 
+/// Say which case each image shows, and complain about images that don't show a real case.
+/// Returns the number of images that didn't match.
+fn report_cases(descs: &[ImageDesc]) -> Result<usize> {
+    let mut unknown = 0;
+    for desc in descs {
+        let found = match &desc.spec {
+            CubeSpec::Oll(oll_spec) => identify_oll(oll_spec).map(|found| found.to_string()),
+            CubeSpec::Pll(pll_spec) => identify_pll(pll_spec)?.map(|found| found.to_string()),
+            // There's no catalog of last layer cases, so only the orientation is checked.
+            CubeSpec::LastLayer(ll_spec) => {
+                identify_oll(&ll_spec.desc).map(|found| found.to_string())
+            }
+        };
+        match found {
+            Some(found) => eprintln!("{}: {}", desc.file_stem, found),
+            None => {
+                eprintln!("warning: {} is not a known case", desc.file_stem);
+                unknown += 1;
+            }
+        }
+    }
+    Ok(unknown)
+}
+
 fn main() -> Result<()> {
     let args: Args = argh::from_env();
 
     let input_reader = BufReader::new(File::open(args.input)?);
     let descs = process_input(input_reader)?;
-    render_descs(&descs, &args.dest_path)?;
+    if args.check {
+        let unknown = report_cases(&descs)?;
+        if unknown > 0 {
+            return Err(anyhow!("{} images do not show a known case", unknown));
+        }
+    }
+    let specs = RenderOpts {
+        colors: args.colors,
+        patterns: args.patterns,
+        ..RenderOpts::with_cubie_size(25)
+    };
+    let format = if args.png {
+        Format::Png { dpi: args.dpi }
+    } else {
+        Format::Svg
+    };
+    render_descs(&descs, &args.dest_path, &specs, &format)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_specs_are_plls() {
+        for spec in ["(4 8 6)", "(1 3)(2 8)", " (UBL UBR UFR)", "1>3 3>9 9>1"] {
+            assert!(
+                matches!(CubeSpec::new(spec), Ok(CubeSpec::Pll(_))),
+                "{}",
+                spec
+            );
+        }
+        assert!(CubeSpec::new("4 8 6").is_err());
+
+        // Routed to the PLL parser, which rejects it because it can't happen on a real cube.
+        let err = CubeSpec::new("(1 3 9)(2 8)").unwrap_err().to_string();
+        assert!(err.contains("cannot happen"), "{}", err);
+    }
+}
@@ -0,0 +1,55 @@
+use cubetools::algspec::parse_algorithm;
+use cubetools::colors::ColorScheme;
+use cubetools::llrender::render;
+use cubetools::llspec::{last_layer_for_algorithm, parse_last_layer, validate_last_layer};
+use cubetools::{RenderOpts, Result};
+
+/// Output an image showing both the orientation and permutation of the last layer.
+#[derive(argh::FromArgs)]
+struct Args {
+    #[argh(positional)]
+    /// orientation and arrows separated by a slash, like "LUR L=R LDR / 1>3>9>1"
+    input: String,
+
+    #[argh(option, default = "25", short = 'w')]
+    /// width of each cubie
+    cubie_size: u32,
+
+    #[argh(option, default = "ColorScheme::default()")]
+    /// color scheme: "western", "japanese", "deuteranopia" or "protanopia"
+    colors: ColorScheme,
+
+    #[argh(switch)]
+    /// draw patterns on the stickers as well as coloring them
+    patterns: bool,
+
+    #[argh(switch, short = 'a')]
+    /// treat the input as an algorithm and draw the case it solves
+    alg: bool,
+}
+
+fn specs_from_args(args: &Args) -> RenderOpts {
+    RenderOpts {
+        colors: args.colors.clone(),
+        patterns: args.patterns,
+        ..RenderOpts::with_cubie_size(args.cubie_size)
+    }
+}
+
+fn main() -> Result<()> {
+    let args: Args = argh::from_env();
+
+    let specs = specs_from_args(&args);
+    let last_layer = if args.alg {
+        last_layer_for_algorithm(&parse_algorithm(&args.input)?)?
+    } else {
+        parse_last_layer(&args.input)?
+    };
+    validate_last_layer(&last_layer)?;
+
+    let svg = render(&last_layer, &specs);
+
+    println!("{}", svg);
+
+    Ok(())
+}
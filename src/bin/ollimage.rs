@@ -1,6 +1,14 @@
-use cubetools::ollrender::render;
-use cubetools::ollspec::parse_desc;
+use cubetools::algspec::parse_algorithm;
+use cubetools::colors::ColorScheme;
+use cubetools::ollrender::scene;
+use cubetools::ollspec::{desc_for_algorithm, parse_desc, validate_desc};
+use cubetools::pngrender::{PngRenderer, SVG_DPI};
+use cubetools::scene::Renderer;
+use cubetools::svgrender::SvgRenderer;
+use cubetools::termrender::TerminalRenderer;
+use cubetools::verify::verify_oll;
 use cubetools::{RenderOpts, Result};
+use std::io::Write;
 
 #[derive(argh::FromArgs)]
 /// Generate a little cubie diagram
@@ -11,20 +19,73 @@ struct Args {
     #[argh(option, default = "25", short = 'w')]
     /// width of each cubie
     cubie_size: u32,
+
+    #[argh(option, default = "ColorScheme::default()")]
+    /// color scheme: "western", "japanese", "deuteranopia" or "protanopia"
+    colors: ColorScheme,
+
+    #[argh(switch)]
+    /// draw patterns on the stickers as well as coloring them
+    patterns: bool,
+
+    #[argh(switch, short = 'a')]
+    /// treat the input as an algorithm and draw the case it solves
+    alg: bool,
+
+    #[argh(option)]
+    /// check that this algorithm solves the case before drawing it
+    verify: Option<String>,
+
+    #[argh(switch)]
+    /// write a PNG image instead of SVG
+    png: bool,
+
+    #[argh(option, default = "SVG_DPI")]
+    /// resolution of the PNG image in dots per inch; 96 makes one pixel per SVG pixel
+    dpi: f64,
+
+    #[argh(switch)]
+    /// show the diagram in the terminal instead of writing an image
+    preview: bool,
 }
 
 fn specs_from_args(args: &Args) -> RenderOpts {
-    RenderOpts::with_cubie_size(args.cubie_size)
+    RenderOpts {
+        colors: args.colors.clone(),
+        patterns: args.patterns,
+        ..RenderOpts::with_cubie_size(args.cubie_size)
+    }
 }
 
 fn main() -> Result<()> {
     let args: Args = argh::from_env();
+    if args.preview && args.png {
+        return Err(anyhow::anyhow!(
+            "--preview shows the diagram in the terminal, so it can't be used with --png"
+        ));
+    }
 
     let specs = specs_from_args(&args);
-    let desc = parse_desc(&args.input)?;
-    let svg = render(&desc, &specs);
+    let desc = if args.alg {
+        desc_for_algorithm(&parse_algorithm(&args.input)?)?
+    } else {
+        parse_desc(&args.input)?
+    };
+    validate_desc(&desc)?;
+    if let Some(alg) = &args.verify {
+        let verification = verify_oll(&desc, &parse_algorithm(alg)?)?;
+        eprintln!("'{}' solves the case with {}", alg, verification);
+    }
 
-    println!("{}", svg);
+    let scene = scene(&desc, &specs);
+    if args.preview {
+        print!("{}", TerminalRenderer::new().render(&scene)?);
+    } else if args.png {
+        let png = PngRenderer::with_dpi(args.dpi).render(&scene)?;
+        std::io::stdout().write_all(&png)?;
+    } else {
+        println!("{}", SvgRenderer::new().render(&scene));
+    }
 
     Ok(())
 }
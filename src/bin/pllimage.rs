@@ -1,6 +1,15 @@
-use cubetools::pllrender::render;
-use cubetools::pllspec::parse_program;
+use cubetools::algspec::parse_algorithm;
+use cubetools::colors::ColorScheme;
+use cubetools::pllcases::{case_by_name, identify};
+use cubetools::pllrender::scene;
+use cubetools::pllspec::{parse_program, program_for_algorithm, validate_program, SideStickers};
+use cubetools::pngrender::{PngRenderer, SVG_DPI};
+use cubetools::scene::Renderer;
+use cubetools::svgrender::SvgRenderer;
+use cubetools::termrender::TerminalRenderer;
+use cubetools::verify::verify_pll;
 use cubetools::RenderOpts;
+use std::io::Write;
 
 type Result<T> = std::result::Result<T, anyhow::Error>;
 
@@ -8,26 +17,105 @@ type Result<T> = std::result::Result<T, anyhow::Error>;
 #[derive(argh::FromArgs)]
 struct Args {
     #[argh(positional)]
+    /// arrows to draw, optionally followed by side stickers like "{BBB RRR FFF LLL}", or a
+    /// case name like "pll:Ua"
     input: String,
 
     #[argh(option, default = "25", short = 'w')]
     /// width of each cubie
     cubie_size: u32,
+
+    #[argh(option, default = "ColorScheme::default()")]
+    /// color scheme: "western", "japanese", "deuteranopia" or "protanopia"
+    colors: ColorScheme,
+
+    #[argh(switch)]
+    /// draw patterns on the stickers as well as coloring them
+    patterns: bool,
+
+    #[argh(switch, short = 'a')]
+    /// treat the input as an algorithm and draw the case it solves
+    alg: bool,
+
+    #[argh(option)]
+    /// check that this algorithm solves the case before drawing it
+    verify: Option<String>,
+
+    #[argh(switch)]
+    /// print the name of the case instead of drawing it
+    identify: bool,
+
+    #[argh(switch, short = 's')]
+    /// color the side stickers to match the arrows, unless the input gives them
+    stickers: bool,
+
+    #[argh(switch)]
+    /// write a PNG image instead of SVG
+    png: bool,
+
+    #[argh(option, default = "SVG_DPI")]
+    /// resolution of the PNG image in dots per inch; 96 makes one pixel per SVG pixel
+    dpi: f64,
+
+    #[argh(switch)]
+    /// show the diagram in the terminal instead of writing an image
+    preview: bool,
 }
 
 fn specs_from_args(args: &Args) -> RenderOpts {
-    RenderOpts::with_cubie_size(args.cubie_size)
+    RenderOpts {
+        colors: args.colors.clone(),
+        patterns: args.patterns,
+        ..RenderOpts::with_cubie_size(args.cubie_size)
+    }
 }
 
 fn main() -> Result<()> {
     let args: Args = argh::from_env();
+    if args.preview && args.png {
+        return Err(anyhow::anyhow!(
+            "--preview shows the diagram in the terminal, so it can't be used with --png"
+        ));
+    }
     let specs = specs_from_args(&args);
 
-    let program = parse_program(&args.input)?;
+    let mut program = if let Some(name) = args.input.strip_prefix("pll:") {
+        case_by_name(name)
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a PLL case name", name))?
+            .program
+            .clone()
+    } else if args.alg {
+        program_for_algorithm(&parse_algorithm(&args.input)?)?
+    } else {
+        parse_program(&args.input)?
+    };
+    validate_program(&program)?;
+    if args.stickers && program.side_stickers.is_none() {
+        program.side_stickers = Some(SideStickers::Auto);
+    }
+
+    if args.identify {
+        match identify(&program)? {
+            Some(found) => println!("{}", found),
+            None => return Err(anyhow::anyhow!("'{}' is not a known PLL case", args.input)),
+        }
+        return Ok(());
+    }
 
-    let svg = render(&program, &specs);
+    if let Some(alg) = &args.verify {
+        let verification = verify_pll(&program, &parse_algorithm(alg)?)?;
+        eprintln!("'{}' solves the case with {}", alg, verification);
+    }
 
-    println!("{}", svg);
+    let scene = scene(&program, &specs);
+    if args.preview {
+        print!("{}", TerminalRenderer::new().render(&scene)?);
+    } else if args.png {
+        let png = PngRenderer::with_dpi(args.dpi).render(&scene)?;
+        std::io::stdout().write_all(&png)?;
+    } else {
+        println!("{}", SvgRenderer::new().render(&scene));
+    }
 
     Ok(())
 }
@@ -41,18 +41,14 @@ fn prettify_table(
     let mut table_lines = vec![];
 
     // Collect the lines of the table into a Vec.
-    loop {
-        if let Some(line) = lines.next_if(|item| {
-            if let Ok(line) = item {
-                line.starts_with('|')
-            } else {
-                false
-            }
-        }) {
-            table_lines.push(line?);
+    while let Some(line) = lines.next_if(|item| {
+        if let Ok(line) = item {
+            line.starts_with('|')
         } else {
-            break;
+            false
         }
+    }) {
+        table_lines.push(line?);
     }
 
     let segment_lists = table_lines
@@ -96,7 +92,7 @@ fn prettify_table(
     Ok(())
 }
 
-fn copy_maxes(maxes: &Vec<usize>, segments: &Vec<&str>) -> Vec<usize> {
+fn copy_maxes(maxes: &[usize], segments: &[&str]) -> Vec<usize> {
     maxes
         .iter()
         .zip(segments.iter())
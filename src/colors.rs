@@ -0,0 +1,301 @@
+use crate::cube::Face;
+use anyhow::anyhow;
+use std::str::FromStr;
+
+/// The colors used to draw a diagram. Any SVG color works, e.g. "yellow" or "#ffd500".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorScheme {
+    pub top: String,
+    pub bottom: String,
+    pub front: String,
+    pub right: String,
+    pub back: String,
+    pub left: String,
+    /// The top of a cubie whose top color faces sideways, in OLL diagrams.
+    pub unoriented: String,
+    /// Stickers that are unknown or don't matter.
+    pub mask: String,
+    pub arrow: String,
+    /// The square behind the cubies.
+    pub background: String,
+    /// The outline of side stickers.
+    pub border: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self::western()
+    }
+}
+
+impl ColorScheme {
+    /// The usual scheme, with yellow on top and green in front.
+    pub fn western() -> Self {
+        ColorScheme {
+            top: "yellow".to_string(),
+            bottom: "white".to_string(),
+            front: "green".to_string(),
+            right: "orange".to_string(),
+            back: "blue".to_string(),
+            left: "red".to_string(),
+            unoriented: "white".to_string(),
+            mask: "gray".to_string(),
+            arrow: "red".to_string(),
+            background: "black".to_string(),
+            border: "black".to_string(),
+        }
+    }
+
+    /// The Japanese scheme swaps blue and yellow, so blue is on top and yellow is in back.
+    pub fn japanese() -> Self {
+        ColorScheme {
+            top: "blue".to_string(),
+            back: "yellow".to_string(),
+            ..Self::western()
+        }
+    }
+
+    /// Colors that stay apart for people who can't tell red from green (deuteranopia). The
+    /// top, unoriented and masked stickers differ in brightness as well as hue.
+    pub fn deuteranopia() -> Self {
+        ColorScheme {
+            top: "#ffb000".to_string(),
+            bottom: "#ffffff".to_string(),
+            front: "#009e73".to_string(),
+            right: "#fe6100".to_string(),
+            back: "#648fff".to_string(),
+            left: "#dc267f".to_string(),
+            unoriented: "#ffffff".to_string(),
+            mask: "#707070".to_string(),
+            arrow: "#785ef0".to_string(),
+            background: "black".to_string(),
+            border: "black".to_string(),
+        }
+    }
+
+    /// Like `deuteranopia`, but without the reds that look dark with protanopia.
+    pub fn protanopia() -> Self {
+        ColorScheme {
+            top: "#f0e442".to_string(),
+            right: "#e69f00".to_string(),
+            back: "#0072b2".to_string(),
+            left: "#cc79a7".to_string(),
+            mask: "#606060".to_string(),
+            arrow: "#56b4e9".to_string(),
+            ..Self::deuteranopia()
+        }
+    }
+
+    /// The color of the stickers on `face` of a solved cube.
+    pub fn face(&self, face: Face) -> &str {
+        match face {
+            Face::U => &self.top,
+            Face::D => &self.bottom,
+            Face::F => &self.front,
+            Face::R => &self.right,
+            Face::B => &self.back,
+            Face::L => &self.left,
+        }
+    }
+
+    pub fn sticker(&self, sticker: Sticker) -> &str {
+        match sticker {
+            Sticker::Face(face) => self.face(face),
+            Sticker::Unoriented => &self.unoriented,
+            Sticker::Mask => &self.mask,
+        }
+    }
+}
+
+/// What a sticker in a diagram shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sticker {
+    /// The color of a face of a solved cube.
+    Face(Face),
+    Unoriented,
+    Mask,
+}
+
+impl Sticker {
+    pub const ALL: [Sticker; 8] = [
+        Sticker::Face(Face::U),
+        Sticker::Face(Face::R),
+        Sticker::Face(Face::F),
+        Sticker::Face(Face::D),
+        Sticker::Face(Face::L),
+        Sticker::Face(Face::B),
+        Sticker::Unoriented,
+        Sticker::Mask,
+    ];
+
+    /// The pattern drawn over the sticker's color when patterns are turned on, so that
+    /// stickers can be told apart without relying on color. The top color is left plain
+    /// since it's the one that matters most.
+    pub fn pattern(self) -> Pattern {
+        match self {
+            Sticker::Face(Face::U) | Sticker::Face(Face::D) => Pattern::Solid,
+            Sticker::Face(Face::F) => Pattern::Hatch,
+            Sticker::Face(Face::R) => Pattern::Dots,
+            Sticker::Face(Face::B) => Pattern::CrossHatch,
+            Sticker::Face(Face::L) => Pattern::HorizontalStripes,
+            Sticker::Unoriented => Pattern::BackHatch,
+            Sticker::Mask => Pattern::VerticalStripes,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    Solid,
+    /// Diagonal lines rising to the right.
+    Hatch,
+    /// Diagonal lines falling to the right.
+    BackHatch,
+    CrossHatch,
+    Dots,
+    HorizontalStripes,
+    VerticalStripes,
+}
+
+/// The red, green and blue parts of a color, for backends that don't understand SVG colors.
+/// Takes "#rgb", "#rrggbb" or one of the common named colors.
+pub fn rgb(color: &str) -> crate::Result<[u8; 3]> {
+    let color = color.trim().to_ascii_lowercase();
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|ch| ch.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<_>>>();
+        return match digits.as_deref() {
+            Some([r, g, b]) => Ok([r * 17, g * 17, b * 17]),
+            Some([r1, r2, g1, g2, b1, b2]) => Ok([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2]),
+            _ => Err(anyhow!(
+                "Bad color, '{}'. Hex colors look like '#ffd500'",
+                color
+            )),
+        };
+    }
+
+    let rgb = match color.as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "silver" => [192, 192, 192],
+        "red" => [255, 0, 0],
+        "maroon" => [128, 0, 0],
+        "orange" => [255, 165, 0],
+        "yellow" => [255, 255, 0],
+        "olive" => [128, 128, 0],
+        "lime" => [0, 255, 0],
+        "green" => [0, 128, 0],
+        "teal" => [0, 128, 128],
+        "aqua" | "cyan" => [0, 255, 255],
+        "blue" => [0, 0, 255],
+        "navy" => [0, 0, 128],
+        "purple" => [128, 0, 128],
+        "fuchsia" | "magenta" => [255, 0, 255],
+        "pink" => [255, 192, 203],
+        "brown" => [165, 42, 42],
+        _ => {
+            return Err(anyhow!(
+                "Unknown color, '{}'. Use a hex color like '#ffd500' instead",
+                color
+            ))
+        }
+    };
+    Ok(rgb)
+}
+
+impl FromStr for ColorScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "western" => Ok(Self::western()),
+            "japanese" => Ok(Self::japanese()),
+            "deuteranopia" => Ok(Self::deuteranopia()),
+            "protanopia" => Ok(Self::protanopia()),
+            _ => Err(anyhow!(
+                "Unknown color scheme, '{}'. Try 'western', 'japanese', 'deuteranopia' or 'protanopia'",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemes_by_name() {
+        assert_eq!(
+            "western".parse::<ColorScheme>().unwrap(),
+            ColorScheme::western()
+        );
+        assert_eq!(
+            "Japanese".parse::<ColorScheme>().unwrap(),
+            ColorScheme::japanese()
+        );
+        assert_eq!(ColorScheme::default(), ColorScheme::western());
+        assert_eq!(ColorScheme::japanese().face(Face::U), "blue");
+        assert_eq!(ColorScheme::japanese().face(Face::B), "yellow");
+
+        let err = "eastern".parse::<ColorScheme>().unwrap_err().to_string();
+        assert!(
+            err.starts_with("Unknown color scheme, 'eastern'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn colorblind_schemes_by_name() {
+        assert_eq!(
+            "deuteranopia".parse::<ColorScheme>().unwrap(),
+            ColorScheme::deuteranopia()
+        );
+        assert_eq!(
+            "PROTANOPIA".parse::<ColorScheme>().unwrap(),
+            ColorScheme::protanopia()
+        );
+    }
+
+    #[test]
+    fn patterns_tell_stickers_apart() {
+        // Only the top and bottom are left plain; where both are drawn, they sit apart.
+        for (i, a) in Sticker::ALL.iter().enumerate() {
+            for b in &Sticker::ALL[i + 1..] {
+                let plain = [Sticker::Face(Face::U), Sticker::Face(Face::D)];
+                if plain.contains(a) && plain.contains(b) {
+                    assert_eq!(a.pattern(), Pattern::Solid);
+                    continue;
+                }
+                assert_ne!(a.pattern(), b.pattern(), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn rgb_colors() {
+        assert_eq!(rgb("#ffd500").unwrap(), [255, 213, 0]);
+        assert_eq!(rgb("#FFD500").unwrap(), [255, 213, 0]);
+        assert_eq!(rgb("#f80").unwrap(), [255, 136, 0]);
+        assert_eq!(rgb(" Orange ").unwrap(), [255, 165, 0]);
+        assert_eq!(rgb("grey").unwrap(), rgb("gray").unwrap());
+
+        for bad in ["#ffd50", "#ggg", "#", "chartreuse", ""] {
+            assert!(rgb(bad).is_err(), "{}", bad);
+        }
+        // Every color in every scheme can be drawn by the raster backends.
+        for scheme in ["western", "japanese", "deuteranopia", "protanopia"] {
+            let scheme = scheme.parse::<ColorScheme>().unwrap();
+            for sticker in Sticker::ALL.iter() {
+                assert!(rgb(scheme.sticker(*sticker)).is_ok(), "{:?}", sticker);
+            }
+            for color in [&scheme.arrow, &scheme.background, &scheme.border] {
+                assert!(rgb(color).is_ok(), "{}", color);
+            }
+        }
+    }
+}
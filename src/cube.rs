@@ -0,0 +1,646 @@
+use crate::Result;
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::str::FromStr;
+
+/// The six faces of the cube. A sticker's "color" is the face it belongs on when the cube
+/// is solved, so `Face` doubles as the color of a facelet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Face {
+    U,
+    R,
+    F,
+    D,
+    L,
+    B,
+}
+
+impl Face {
+    /// All faces, in facelet order.
+    pub const ALL: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            Face::U => 'U',
+            Face::R => 'R',
+            Face::F => 'F',
+            Face::D => 'D',
+            Face::L => 'L',
+            Face::B => 'B',
+        }
+    }
+
+    pub fn from_letter(ch: char) -> Option<Face> {
+        Face::ALL.iter().copied().find(|face| face.letter() == ch)
+    }
+
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::U => Face::D,
+            Face::R => Face::L,
+            Face::F => Face::B,
+            Face::D => Face::U,
+            Face::L => Face::R,
+            Face::B => Face::F,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Slice {
+    /// Middle layer, turning like L.
+    M,
+    /// Equatorial layer, turning like D.
+    E,
+    /// Standing layer, turning like F.
+    S,
+}
+
+/// The part of the cube that a move turns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// A single outer layer: R, U, F, D, L, B.
+    Face(Face),
+    /// An outer layer plus the adjacent slice: r, u, f, d, l, b (or Rw, Uw, ...).
+    Wide(Face),
+    /// A single inner layer: M, E, S.
+    Slice(Slice),
+    /// The whole cube: x, y, z.
+    Rotation(Axis),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Amount {
+    Clockwise,
+    Double,
+    CounterClockwise,
+}
+
+impl Amount {
+    /// Build an Amount from a number of clockwise quarter turns. Returns None for multiples of 4.
+    pub fn from_quarter_turns(turns: u32) -> Option<Amount> {
+        match turns % 4 {
+            1 => Some(Amount::Clockwise),
+            2 => Some(Amount::Double),
+            3 => Some(Amount::CounterClockwise),
+            _ => None,
+        }
+    }
+
+    pub fn quarter_turns(self) -> u32 {
+        match self {
+            Amount::Clockwise => 1,
+            Amount::Double => 2,
+            Amount::CounterClockwise => 3,
+        }
+    }
+
+    pub fn inverse(self) -> Amount {
+        match self {
+            Amount::Clockwise => Amount::CounterClockwise,
+            Amount::Double => Amount::Double,
+            Amount::CounterClockwise => Amount::Clockwise,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub layer: Layer,
+    pub amount: Amount,
+}
+
+impl Move {
+    pub fn new(layer: Layer, amount: Amount) -> Move {
+        Move { layer, amount }
+    }
+
+    /// The U turn for an adjustment of `turns` clockwise quarter turns, or None if the
+    /// U layer doesn't need adjusting.
+    pub fn auf(turns: u32) -> Option<Move> {
+        Amount::from_quarter_turns(turns).map(|amount| Move::new(Layer::Face(Face::U), amount))
+    }
+
+    pub fn inverse(self) -> Move {
+        Move {
+            layer: self.layer,
+            amount: self.amount.inverse(),
+        }
+    }
+}
+
+/// The adjustments to try when looking for an AUF, in quarter turns for `Move::auf`: none
+/// first, then the quarter turns, then the half turn.
+pub const AUF_ORDER: [u32; 4] = [0, 1, 3, 2];
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.layer {
+            Layer::Face(face) => write!(f, "{}", face.letter())?,
+            Layer::Wide(face) => write!(f, "{}w", face.letter())?,
+            Layer::Slice(slice) => write!(f, "{:?}", slice)?,
+            Layer::Rotation(axis) => write!(f, "{}", format!("{:?}", axis).to_lowercase())?,
+        }
+        match self.amount {
+            Amount::Clockwise => Ok(()),
+            Amount::Double => write!(f, "2"),
+            Amount::CounterClockwise => write!(f, "'"),
+        }
+    }
+}
+
+type Vector = [i8; 3];
+
+/// The location of every facelet in space: the position of its cubie and the direction its
+/// sticker faces. The cube is centered on the origin with x pointing towards R, y towards U
+/// and z towards F.
+///
+/// Facelets are numbered face by face in the order U, R, F, D, L, B, with each face read in
+/// rows from the upper-left as it appears on the usual unfolded net:
+///
+/// ```text
+///              U0 U1 U2
+///              U3 U4 U5
+///              U6 U7 U8
+///    L0 L1 L2  F0 F1 F2  R0 R1 R2  B0 B1 B2
+///    L3 L4 L5  F3 F4 F5  R3 R4 R5  B3 B4 B5
+///    L6 L7 L8  F6 F7 F8  R6 R7 R8  B6 B7 B8
+///              D0 D1 D2
+///              D3 D4 D5
+///              D6 D7 D8
+/// ```
+static FACELETS: Lazy<Vec<(Vector, Vector)>> = Lazy::new(|| {
+    let mut facelets = Vec::with_capacity(54);
+    for face in Face::ALL.iter() {
+        for idx in 0..9 {
+            let row = idx / 3 - 1;
+            let col = idx % 3 - 1;
+            let facelet = match face {
+                Face::U => ([col, 1, row], [0, 1, 0]),
+                Face::R => ([1, -row, -col], [1, 0, 0]),
+                Face::F => ([col, -row, 1], [0, 0, 1]),
+                Face::D => ([col, -1, -row], [0, -1, 0]),
+                Face::L => ([-1, -row, col], [-1, 0, 0]),
+                Face::B => ([-col, -row, -1], [0, 0, -1]),
+            };
+            facelets.push(facelet);
+        }
+    }
+    facelets
+});
+
+/// A quarter turn, clockwise when looking at the cube from the positive end of `axis`.
+/// `layers` lists the coordinates along the axis of the slices that move.
+struct QuarterTurn {
+    axis: Axis,
+    layers: &'static [i8],
+    clockwise: bool,
+}
+
+impl QuarterTurn {
+    fn for_layer(layer: Layer) -> QuarterTurn {
+        use Axis::*;
+
+        let (axis, layers, clockwise): (Axis, &'static [i8], bool) = match layer {
+            Layer::Face(Face::R) => (X, &[1], true),
+            Layer::Face(Face::L) => (X, &[-1], false),
+            Layer::Face(Face::U) => (Y, &[1], true),
+            Layer::Face(Face::D) => (Y, &[-1], false),
+            Layer::Face(Face::F) => (Z, &[1], true),
+            Layer::Face(Face::B) => (Z, &[-1], false),
+            Layer::Wide(Face::R) => (X, &[0, 1], true),
+            Layer::Wide(Face::L) => (X, &[-1, 0], false),
+            Layer::Wide(Face::U) => (Y, &[0, 1], true),
+            Layer::Wide(Face::D) => (Y, &[-1, 0], false),
+            Layer::Wide(Face::F) => (Z, &[0, 1], true),
+            Layer::Wide(Face::B) => (Z, &[-1, 0], false),
+            Layer::Slice(Slice::M) => (X, &[0], false),
+            Layer::Slice(Slice::E) => (Y, &[0], false),
+            Layer::Slice(Slice::S) => (Z, &[0], true),
+            Layer::Rotation(X) => (X, &[-1, 0, 1], true),
+            Layer::Rotation(Y) => (Y, &[-1, 0, 1], true),
+            Layer::Rotation(Z) => (Z, &[-1, 0, 1], true),
+        };
+
+        QuarterTurn {
+            axis,
+            layers,
+            clockwise,
+        }
+    }
+
+    fn moves(&self, pos: Vector) -> bool {
+        let coord = match self.axis {
+            Axis::X => pos[0],
+            Axis::Y => pos[1],
+            Axis::Z => pos[2],
+        };
+        self.layers.contains(&coord)
+    }
+
+    fn rotate(&self, v: Vector) -> Vector {
+        let turns = if self.clockwise { 1 } else { 3 };
+        (0..turns).fold(v, |[x, y, z], _| match self.axis {
+            Axis::X => [x, z, -y],
+            Axis::Y => [-z, y, x],
+            Axis::Z => [y, -x, z],
+        })
+    }
+}
+
+fn facelet_at(pos: Vector, normal: Vector) -> usize {
+    // unwrap: rotations always map facelets onto facelets.
+    FACELETS
+        .iter()
+        .position(|facelet| *facelet == (pos, normal))
+        .unwrap()
+}
+
+/// Where facelet `idx` is in space: the position of its cubie, with each coordinate in -1..=1,
+/// and the direction its sticker faces. See `FACELETS` for the axes.
+pub fn facelet_geometry(idx: usize) -> ([i8; 3], [i8; 3]) {
+    FACELETS[idx]
+}
+
+/// The face that facelet `idx` lies on.
+pub fn face_of_facelet(idx: usize) -> Face {
+    Face::ALL[idx / 9]
+}
+
+/// Where each U layer position ends up after a clockwise quarter turn of the U layer, with the
+/// positions numbered like the U face.
+pub const U_TURN: [u8; 9] = [2, 5, 8, 1, 4, 7, 0, 3, 6];
+
+/// The facelets of the U layer cubie at `idx`, where the U layer is numbered like the U face:
+///
+/// ```text
+///    0 1 2
+///    3 4 5
+///    6 7 8
+/// ```
+///
+/// with the back of the cube at the top.
+pub fn top_layer_facelets(idx: usize) -> Vec<usize> {
+    let pos = [idx as i8 % 3 - 1, 1, idx as i8 / 3 - 1];
+    FACELETS
+        .iter()
+        .enumerate()
+        .filter(|(_, (facelet_pos, _))| *facelet_pos == pos)
+        .map(|(facelet_idx, _)| facelet_idx)
+        .collect()
+}
+
+/// A 3x3 cube, stored as the colors of its 54 facelets.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cube {
+    facelets: [Face; 54],
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self::solved()
+    }
+}
+
+impl Cube {
+    pub fn solved() -> Self {
+        let mut facelets = [Face::U; 54];
+        for (idx, facelet) in facelets.iter_mut().enumerate() {
+            *facelet = Face::ALL[idx / 9];
+        }
+        Cube { facelets }
+    }
+
+    pub fn facelets(&self) -> &[Face; 54] {
+        &self.facelets
+    }
+
+    pub fn facelet(&self, idx: usize) -> Face {
+        self.facelets[idx]
+    }
+
+    /// The nine facelets of one face, in the order shown on the net above.
+    pub fn face(&self, face: Face) -> [Face; 9] {
+        let mut result = [face; 9];
+        result.copy_from_slice(&self.facelets[face.index() * 9..face.index() * 9 + 9]);
+        result
+    }
+
+    /// The color of the center of `face`. Centers only move with slices and rotations.
+    pub fn center(&self, face: Face) -> Face {
+        self.facelets[face.index() * 9 + 4]
+    }
+
+    /// True if every face is a single color. The cube may be in any orientation.
+    pub fn is_solved(&self) -> bool {
+        Face::ALL.iter().all(|face| {
+            let center = self.center(*face);
+            self.face(*face).iter().all(|facelet| *facelet == center)
+        })
+    }
+
+    /// Twist the U layer cubie at `idx` in place so that the sticker on the U face moves to
+    /// `up_facelet`, which must be one of the cubie's facelets.
+    pub fn twist_top_layer_cubie(&mut self, idx: usize, up_facelet: usize) -> Result<()> {
+        // The U facelet always has the lowest index, so it comes first.
+        let facelets = top_layer_facelets(idx);
+        let shift = facelets
+            .iter()
+            .position(|facelet| *facelet == up_facelet)
+            .ok_or_else(|| anyhow!("Facelet {} is not part of cubie {}", up_facelet, idx))?;
+
+        // Shifting the colors around the cubie keeps them in the same cyclic order, so a
+        // corner is twisted rather than reflected.
+        let old = self.facelets;
+        for (pos, facelet) in facelets.iter().enumerate() {
+            self.facelets[facelets[(pos + shift) % facelets.len()]] = old[*facelet];
+        }
+        Ok(())
+    }
+
+    /// Move each U layer cubie from `idx` to `targets[idx]` as if by turning the U layer, so the
+    /// stickers on the U face stay there.
+    pub fn permute_top_layer(&mut self, targets: &[u8; 9]) -> Result<()> {
+        let u_turn = QuarterTurn::for_layer(Layer::Face(Face::U));
+        let old = self.facelets;
+
+        for (idx, target) in targets.iter().enumerate() {
+            // The U facelets are numbered like the cubies, so they give the cubie positions.
+            let mut pos = FACELETS[idx].0;
+            let target_pos = FACELETS[*target as usize].0;
+            let mut turns = 0;
+            while pos != target_pos {
+                if turns == 3 {
+                    return Err(anyhow!(
+                        "Cannot turn cubie {} into position {}",
+                        idx,
+                        target
+                    ));
+                }
+                pos = u_turn.rotate(pos);
+                turns += 1;
+            }
+
+            for facelet in top_layer_facelets(idx) {
+                let (mut pos, mut normal) = FACELETS[facelet];
+                for _ in 0..turns {
+                    pos = u_turn.rotate(pos);
+                    normal = u_turn.rotate(normal);
+                }
+                self.facelets[facelet_at(pos, normal)] = old[facelet];
+            }
+        }
+        Ok(())
+    }
+
+    /// True if everything below the U layer matches its center, so the cube is solved apart
+    /// from the last layer.
+    pub fn is_first_two_layers_solved(&self) -> bool {
+        FACELETS
+            .iter()
+            .enumerate()
+            .filter(|(_, (pos, _))| pos[1] < 1)
+            .all(|(idx, _)| self.facelets[idx] == self.center(face_of_facelet(idx)))
+    }
+
+    pub fn apply(&mut self, mv: Move) {
+        let turn = QuarterTurn::for_layer(mv.layer);
+        for _ in 0..mv.amount.quarter_turns() {
+            self.apply_quarter_turn(&turn);
+        }
+    }
+
+    pub fn apply_all<'a>(&mut self, moves: impl IntoIterator<Item = &'a Move>) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+
+    fn apply_quarter_turn(&mut self, turn: &QuarterTurn) {
+        let old = self.facelets;
+        for (idx, (pos, normal)) in FACELETS.iter().enumerate() {
+            if turn.moves(*pos) {
+                let dest = facelet_at(turn.rotate(*pos), turn.rotate(*normal));
+                self.facelets[dest] = old[idx];
+            }
+        }
+    }
+}
+
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for facelet in self.facelets.iter() {
+            write!(f, "{}", facelet.letter())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a cube from its 54 facelets in the order that Display writes them: URFDLB, each
+/// face read in rows as shown on the net above, with each facelet given by the face whose
+/// color it has. Whitespace is ignored, so the faces can be separated.
+impl FromStr for Cube {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let letters = s
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .collect::<Vec<_>>();
+        if letters.len() != 54 {
+            return Err(anyhow!(
+                "A cube has 54 facelets, but '{}' has {}",
+                s,
+                letters.len()
+            ));
+        }
+
+        let mut facelets = [Face::U; 54];
+        for (facelet, ch) in facelets.iter_mut().zip(letters.iter()) {
+            *facelet = Face::from_letter(ch.to_ascii_uppercase())
+                .ok_or_else(|| anyhow!("Unknown facelet, '{}', in '{}'", ch, s))?;
+        }
+
+        for face in Face::ALL.iter() {
+            let count = facelets.iter().filter(|facelet| *facelet == face).count();
+            if count != 9 {
+                return Err(anyhow!(
+                    "A cube has 9 facelets of each color, but '{}' has {} {}s",
+                    s,
+                    count,
+                    face.letter()
+                ));
+            }
+        }
+
+        Ok(Cube { facelets })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(layer: Layer, amount: Amount) -> Move {
+        Move::new(layer, amount)
+    }
+
+    fn face_turn(face: Face) -> Move {
+        mv(Layer::Face(face), Amount::Clockwise)
+    }
+
+    fn after(moves: &[Move]) -> Cube {
+        let mut cube = Cube::solved();
+        cube.apply_all(moves);
+        cube
+    }
+
+    #[test]
+    fn solved_cube() {
+        let cube = Cube::solved();
+        assert!(cube.is_solved());
+        assert!(cube.is_first_two_layers_solved());
+        assert_eq!(
+            cube.to_string(),
+            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+        );
+    }
+
+    #[test]
+    fn face_turns_move_the_right_facelets() {
+        let expected = [
+            (
+                Face::U,
+                "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB",
+            ),
+            (
+                Face::R,
+                "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB",
+            ),
+            (
+                Face::F,
+                "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB",
+            ),
+            (
+                Face::D,
+                "UUUUUUUUURRRRRRFFFFFFFFFLLLDDDDDDDDDLLLLLLBBBBBBBBBRRR",
+            ),
+            (
+                Face::L,
+                "BUUBUUBUURRRRRRRRRUFFUFFUFFFDDFDDFDDLLLLLLLLLBBDBBDBBD",
+            ),
+            (
+                Face::B,
+                "RRRUUUUUURRDRRDRRDFFFFFFFFFDDDDDDLLLULLULLULLBBBBBBBBB",
+            ),
+        ];
+        for (face, facelets) in expected.iter() {
+            assert_eq!(
+                after(&[face_turn(*face)]).to_string(),
+                *facelets,
+                "{:?}",
+                face
+            );
+        }
+    }
+
+    #[test]
+    fn every_move_has_order_four_and_an_inverse() {
+        let mut layers = vec![];
+        for face in Face::ALL.iter() {
+            layers.push(Layer::Face(*face));
+            layers.push(Layer::Wide(*face));
+        }
+        layers.extend(
+            [Slice::M, Slice::E, Slice::S]
+                .iter()
+                .map(|s| Layer::Slice(*s)),
+        );
+        layers.extend(
+            [Axis::X, Axis::Y, Axis::Z]
+                .iter()
+                .map(|a| Layer::Rotation(*a)),
+        );
+
+        for layer in layers {
+            let turn = mv(layer, Amount::Clockwise);
+            assert_ne!(after(&[turn]), Cube::solved(), "{}", turn);
+            assert!(after(&[turn; 4]).is_solved(), "{}", turn);
+            assert!(after(&[turn, turn.inverse()]).is_solved(), "{}", turn);
+            assert_eq!(
+                after(&[mv(layer, Amount::Double)]),
+                after(&[turn, turn]),
+                "{}",
+                turn
+            );
+        }
+    }
+
+    #[test]
+    fn wide_moves_and_rotations_combine_layers() {
+        let r = face_turn(Face::R);
+        let l_prime = mv(Layer::Face(Face::L), Amount::CounterClockwise);
+        let m_prime = mv(Layer::Slice(Slice::M), Amount::CounterClockwise);
+        assert_eq!(
+            after(&[mv(Layer::Wide(Face::R), Amount::Clockwise)]),
+            after(&[r, m_prime])
+        );
+        assert_eq!(
+            after(&[mv(Layer::Rotation(Axis::X), Amount::Clockwise)]),
+            after(&[r, m_prime, l_prime])
+        );
+    }
+
+    #[test]
+    fn sexy_move_has_order_six() {
+        let r = face_turn(Face::R);
+        let u = face_turn(Face::U);
+        let sexy = [r, u, r.inverse(), u.inverse()];
+        let mut cube = Cube::solved();
+        for count in 1..=6 {
+            cube.apply_all(&sexy);
+            assert_eq!(cube.is_solved(), count == 6);
+        }
+    }
+
+    #[test]
+    fn rotations_keep_the_cube_solved_but_move_centers() {
+        let cube = after(&[mv(Layer::Rotation(Axis::Y), Amount::Clockwise)]);
+        assert!(cube.is_solved());
+        assert_eq!(cube.center(Face::F), Face::R);
+    }
+
+    #[test]
+    fn facelet_geometry_matches_faces() {
+        for idx in 0..54 {
+            let (pos, normal) = facelet_geometry(idx);
+            let face = face_of_facelet(idx);
+            let center = facelet_geometry(face.index() * 9 + 4);
+            assert_eq!(normal, center.1, "facelet {}", idx);
+            let axis = normal.iter().position(|n| *n != 0).unwrap();
+            assert_eq!(pos[axis], normal[axis], "facelet {}", idx);
+        }
+        assert_eq!(facelet_geometry(4), ([0, 1, 0], [0, 1, 0]));
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        let cube = after(&[face_turn(Face::R), face_turn(Face::U)]);
+        assert_eq!(cube.to_string().parse::<Cube>().unwrap(), cube);
+        assert!("UUU".parse::<Cube>().is_err());
+        assert!("X".repeat(54).parse::<Cube>().is_err());
+        assert!("U".repeat(54).parse::<Cube>().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+
+pub type ParseResult<T> = std::result::Result<T, ParseError>;
+
+/// An error found while parsing one of the spec languages. It remembers where in the input the
+/// problem was and what the parser would have accepted there, and its Display output points
+/// at the offending character:
+///
+/// ```text
+/// Unexpected 'x' while parsing Cubie, expected one of '1'-'9'
+///   1>3 x>9
+///       ^
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    source: String,
+    // Bytes left in the input when the error was found. The parsers only see the tail of
+    // their input, so the offset is fixed up by `locate` once the whole input is known.
+    remaining: usize,
+    expected: Vec<String>,
+    message: String,
+}
+
+impl ParseError {
+    /// An error at the start of `tail`, which must be a suffix of the input being parsed.
+    pub(crate) fn new(tail: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            source: String::new(),
+            remaining: tail.len(),
+            expected: vec![],
+            message: message.into(),
+        }
+    }
+
+    /// An error for whatever character starts `tail`, or for the end of input.
+    pub(crate) fn unexpected(tail: &str, parsing: &str) -> ParseError {
+        let message = match tail.chars().next() {
+            Some(ch) => format!("Unexpected '{}' while parsing {}", ch, parsing),
+            None => format!("Unexpected end of input while parsing {}", parsing),
+        };
+        ParseError::new(tail, message)
+    }
+
+    pub(crate) fn expecting<I, S>(mut self, expected: I) -> ParseError
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.expected.extend(expected.into_iter().map(Into::into));
+        self
+    }
+
+    /// Attach the full input that the error was found in.
+    pub(crate) fn locate(mut self, source: &str) -> ParseError {
+        self.source = source.to_string();
+        self.remaining = self.remaining.min(source.len());
+        self
+    }
+
+    /// The input that failed to parse.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Byte offset into `source` where the problem was found. It always falls on a character
+    /// boundary, so it's safe to slice `source` with it.
+    pub fn offset(&self) -> usize {
+        let mut offset = self.source.len().saturating_sub(self.remaining);
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// The tokens that would have been accepted at `offset`, if known.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        match self.expected.as_slice() {
+            [] => {}
+            [only] => write!(f, ", expected {}", only)?,
+            expected => write!(f, ", expected one of {}", expected.join(", "))?,
+        }
+
+        // Tabs and other control characters would throw off the caret, so they are shown as
+        // spaces. The caret is placed by character, not byte, so multibyte input lines up.
+        let line = self
+            .source
+            .chars()
+            .map(|ch| if ch.is_control() { ' ' } else { ch })
+            .collect::<String>();
+        let column = self.source[..self.offset()].chars().count();
+        write!(f, "\n  {}\n  {}^", line, " ".repeat(column))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret_points_at_the_error() {
+        let source = "1>3 x>9";
+        let err = ParseError::unexpected(&source[4..], "Cubie")
+            .expecting(["'1'-'9'"])
+            .locate(source);
+        assert_eq!(err.offset(), 4);
+        assert_eq!(
+            err.to_string(),
+            "Unexpected 'x' while parsing Cubie, expected '1'-'9'\n  1>3 x>9\n      ^"
+        );
+    }
+
+    #[test]
+    fn caret_counts_characters_not_bytes() {
+        let source = "é\tx";
+        let err = ParseError::unexpected(&source[3..], "Cubie").locate(source);
+        assert_eq!(err.offset(), 3);
+        assert_eq!(
+            err.to_string(),
+            "Unexpected 'x' while parsing Cubie\n  é x\n    ^"
+        );
+    }
+
+    #[test]
+    fn offset_stays_on_a_char_boundary() {
+        // Two bytes remaining would put the offset inside the 'é'.
+        let err = ParseError::new("xy", "Oops").locate("éa");
+        assert_eq!(err.offset(), 0);
+        assert_eq!(err.to_string(), "Oops\n  éa\n  ^");
+
+        let err = ParseError::new("a longer tail than the source", "Oops").locate("é");
+        assert_eq!(err.offset(), 0);
+        assert_eq!(ParseError::new("tail", "Oops").offset(), 0);
+    }
+}
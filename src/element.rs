@@ -0,0 +1,208 @@
+use std::fmt;
+
+/// An XML element with its attributes and children, written out with `pretty` or `compact`.
+/// Attribute values and text are escaped as they are written, so they can hold anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Self {
+        Node::Element(element)
+    }
+}
+
+impl From<&str> for Node {
+    fn from(text: &str) -> Self {
+        Node::Text(text.to_string())
+    }
+}
+
+impl From<String> for Node {
+    fn from(text: String) -> Self {
+        Node::Text(text)
+    }
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn attr(mut self, attr: &str, value: &str) -> Self {
+        self.attrs.push((attr.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn child(mut self, child: impl Into<Node>) -> Self {
+        self.push(child);
+        self
+    }
+
+    pub fn children<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Node>,
+    {
+        self.children.extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn push(&mut self, child: impl Into<Node>) {
+        self.children.push(child.into());
+    }
+
+    /// Each attribute and child on its own line, with a closing tag even when there are no
+    /// children.
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        self.write_pretty(&mut output);
+        output
+    }
+
+    /// Everything on one line, with no whitespace between elements.
+    pub fn compact(&self) -> String {
+        let mut output = String::new();
+        self.write_compact(&mut output);
+        output
+    }
+
+    fn write_pretty(&self, output: &mut String) {
+        output.push('<');
+        output.push_str(&self.name);
+        output.push('\n');
+        let attrs = self
+            .attrs
+            .iter()
+            .map(|(a, v)| format!("   {}=\"{}\"", a, escape_attr(v)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        output.push_str(&attrs);
+        output.push_str(">\n");
+
+        for child in &self.children {
+            match child {
+                Node::Element(element) => element.write_pretty(output),
+                Node::Text(text) => {
+                    output.push_str(&escape_text(text));
+                    output.push('\n');
+                }
+            }
+        }
+
+        output.push_str("</");
+        output.push_str(&self.name);
+        output.push_str(">\n");
+    }
+
+    fn write_compact(&self, output: &mut String) {
+        output.push('<');
+        output.push_str(&self.name);
+        for (attr, value) in &self.attrs {
+            output.push_str(&format!(" {}=\"{}\"", attr, escape_attr(value)));
+        }
+
+        if self.children.is_empty() {
+            output.push_str("/>");
+            return;
+        }
+
+        output.push('>');
+        for child in &self.children {
+            match child {
+                Node::Element(element) => element.write_compact(output),
+                Node::Text(text) => output.push_str(&escape_text(text)),
+            }
+        }
+        output.push_str("</");
+        output.push_str(&self.name);
+        output.push('>');
+    }
+}
+
+/// `{}` writes the element compactly and `{:#}` writes it pretty.
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.pretty())
+        } else {
+            f.write_str(&self.compact())
+        }
+    }
+}
+
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn escape_attr(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested() -> Element {
+        Element::new("svg").attr("width", "10").child(
+            Element::new("g")
+                .child(Element::new("rect").attr("x", "1"))
+                .child(Element::new("text").child("hi")),
+        )
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(
+            escape_text("a < b && c > \"d\""),
+            "a &lt; b &amp;&amp; c &gt; \"d\""
+        );
+        assert_eq!(escape_attr("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+
+        let element = Element::new("text")
+            .attr("title", "\"<&>\"")
+            .child("1 < 2 & \"3\"");
+        assert_eq!(
+            element.compact(),
+            "<text title=\"&quot;&lt;&amp;&gt;&quot;\">1 &lt; 2 &amp; \"3\"</text>"
+        );
+        assert_eq!(
+            element.pretty(),
+            "<text\n   title=\"&quot;&lt;&amp;&gt;&quot;\">\n1 &lt; 2 &amp; \"3\"\n</text>\n"
+        );
+    }
+
+    #[test]
+    fn compact() {
+        assert_eq!(
+            nested().compact(),
+            "<svg width=\"10\"><g><rect x=\"1\"/><text>hi</text></g></svg>"
+        );
+        assert_eq!(Element::new("x").compact(), "<x/>");
+        assert_eq!(nested().to_string(), nested().compact());
+    }
+
+    #[test]
+    fn pretty() {
+        assert_eq!(
+            nested().pretty(),
+            "<svg\n   width=\"10\">\n<g\n>\n<rect\n   x=\"1\">\n</rect>\n<text\n>\nhi\n</text>\n</g>\n</svg>\n"
+        );
+        assert_eq!(Element::new("x").pretty(), "<x\n>\n</x>\n");
+        assert_eq!(format!("{:#}", nested()), nested().pretty());
+    }
+}
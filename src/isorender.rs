@@ -0,0 +1,223 @@
+use crate::colors::Sticker;
+use crate::cube::{facelet_geometry, Cube, Face};
+use crate::rendering::sticker_paint;
+use crate::scene::{Renderer, Scene, Shape};
+use crate::svgrender::SvgRenderer;
+use crate::RenderOpts;
+
+/// The direction the cube is seen from. The cube is first turned `yaw` degrees about the
+/// vertical axis, bringing R towards the viewer, then tipped `pitch` degrees towards the
+/// viewer to show the top. Faces pointing away from the viewer aren't drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    pub yaw: f64,
+    pub pitch: f64,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Self::isometric()
+    }
+}
+
+impl Projection {
+    /// Equal parts of U, F and R.
+    pub fn isometric() -> Self {
+        Projection {
+            yaw: 45.0,
+            pitch: (1.0 / 2f64.sqrt()).atan().to_degrees(),
+        }
+    }
+
+    /// Rotate `point` into view space, where x is right, y is up and z points at the viewer.
+    fn view(&self, point: [f64; 3]) -> [f64; 3] {
+        let (yaw_sin, yaw_cos) = self.yaw.to_radians().sin_cos();
+        let (pitch_sin, pitch_cos) = self.pitch.to_radians().sin_cos();
+
+        let [x, y, z] = point;
+        let (x, z) = (x * yaw_cos - z * yaw_sin, x * yaw_sin + z * yaw_cos);
+        let (y, z) = (y * pitch_cos - z * pitch_sin, y * pitch_sin + z * pitch_cos);
+        [x, y, z]
+    }
+
+    fn faces_viewer(&self, normal: [f64; 3]) -> bool {
+        self.view(normal)[2] > 1e-9
+    }
+}
+
+/// Draw the cube in isometric projection, showing U, F and R.
+pub fn render(cube: &Cube, specs: &RenderOpts) -> String {
+    render_with_projection(cube, &Projection::isometric(), specs)
+}
+
+pub fn render_with_projection(cube: &Cube, projection: &Projection, specs: &RenderOpts) -> String {
+    SvgRenderer::new().render(&scene(cube, projection, specs))
+}
+
+pub fn scene(cube: &Cube, projection: &Projection, specs: &RenderOpts) -> Scene {
+    let scale = specs.cubie_size as f64;
+    let border = specs.border_width as f64 / scale;
+    let half_gutter = specs.gutter_size as f64 / scale / 2.0;
+
+    // The screen is laid out around the cube's corners, including the border around each face.
+    let extent = 1.5 + border;
+    let mut corners = vec![];
+    for x in &[-extent, extent] {
+        for y in &[-extent, extent] {
+            for z in &[-extent, extent] {
+                corners.push(projection.view([*x, *y, *z]));
+            }
+        }
+    }
+    let min_x = corners.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p[0])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = corners.iter().map(|p| p[1]).fold(f64::INFINITY, f64::min);
+    let max_y = corners
+        .iter()
+        .map(|p| p[1])
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let to_screen = |point: [f64; 3]| {
+        let [x, y, _] = projection.view(point);
+        ((x - min_x) * scale, (max_y - y) * scale)
+    };
+
+    let width = ((max_x - min_x) * scale).ceil();
+    let height = ((max_y - min_y) * scale).ceil();
+    let mut scene = Scene::new(width, height);
+
+    for face in Face::ALL.iter() {
+        let normal = face_normal(*face);
+        if !projection.faces_viewer(normal) {
+            continue;
+        }
+
+        let points = square(scale_vector(normal, 1.5), normal, 1.5 + border)
+            .iter()
+            .map(|corner| to_screen(*corner))
+            .collect::<Vec<_>>();
+        scene.push(Shape::Polygon {
+            points,
+            fill: specs.colors.background.as_str().into(),
+            stroke: None,
+        });
+
+        for idx in face.index() * 9..face.index() * 9 + 9 {
+            let (pos, normal) = facelet_geometry(idx);
+            let normal = to_f64(normal);
+            let center = add(to_f64(pos), scale_vector(normal, 0.5));
+            let points = square(center, normal, 0.5 - half_gutter)
+                .iter()
+                .map(|corner| to_screen(*corner))
+                .collect::<Vec<_>>();
+            scene.push(Shape::Polygon {
+                points,
+                fill: sticker_paint(Sticker::Face(cube.facelet(idx)), specs),
+                stroke: None,
+            });
+        }
+    }
+
+    scene
+}
+
+fn face_normal(face: Face) -> [f64; 3] {
+    // The center facelet of each face sits at the middle of the face.
+    to_f64(facelet_geometry(face.index() * 9 + 4).1)
+}
+
+/// The corners, in order around the edge, of a square facing `normal` with its center at
+/// `center` and sides `half_size * 2` long.
+fn square(center: [f64; 3], normal: [f64; 3], half_size: f64) -> [[f64; 3]; 4] {
+    // The two axes the normal doesn't point along span the square.
+    let mut axes = (0..3).filter(|axis| normal[*axis] == 0.0).map(|axis| {
+        let mut unit = [0.0; 3];
+        unit[axis] = half_size;
+        unit
+    });
+    // unwrap: a face normal points along exactly one axis.
+    let u = axes.next().unwrap();
+    let v = axes.next().unwrap();
+
+    [
+        add(add(center, u), v),
+        add(sub(center, u), v),
+        sub(sub(center, u), v),
+        sub(add(center, u), v),
+    ]
+}
+
+fn to_f64(vector: [i8; 3]) -> [f64; 3] {
+    [vector[0] as f64, vector[1] as f64, vector[2] as f64]
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale_vector(a: [f64; 3], factor: f64) -> [f64; 3] {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::ColorScheme;
+    use std::collections::BTreeSet;
+
+    fn fills(projection: &Projection) -> Vec<String> {
+        let specs = RenderOpts::with_cubie_size(30);
+        scene(&Cube::solved(), projection, &specs)
+            .shapes
+            .iter()
+            .map(|shape| match shape {
+                Shape::Polygon { fill, .. } => fill.color().to_string(),
+                _ => panic!("expected only polygons, got {:?}", shape),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn isometric_shows_u_f_and_r() {
+        let colors = ColorScheme::default();
+        let fills = fills(&Projection::default());
+        // A background and nine stickers for each visible face.
+        assert_eq!(fills.len(), 30);
+        assert_eq!(
+            fills
+                .iter()
+                .filter(|fill| **fill == colors.background)
+                .count(),
+            3
+        );
+        let stickers = fills
+            .iter()
+            .filter(|fill| **fill != colors.background)
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+        let expected = [Face::U, Face::F, Face::R]
+            .iter()
+            .map(|face| colors.face(*face))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(stickers, expected);
+    }
+
+    #[test]
+    fn looking_from_below_shows_d() {
+        let colors = ColorScheme::default();
+        let fills = fills(&Projection {
+            yaw: 45.0,
+            pitch: -30.0,
+        });
+        assert_eq!(fills.len(), 30);
+        assert!(fills.iter().any(|fill| fill == colors.face(Face::D)));
+        assert!(!fills.iter().any(|fill| fill == colors.face(Face::U)));
+    }
+}
@@ -1,15 +1,33 @@
+pub mod algspec;
+pub mod colors;
+pub mod cube;
+pub mod diagnostics;
+mod element;
+pub mod isorender;
+pub mod llrender;
+pub mod llspec;
+pub mod netrender;
+pub mod ollcases;
 pub mod ollrender;
 pub mod ollspec;
 mod path;
+pub mod pllcases;
 pub mod pllrender;
 pub mod pllspec;
-mod tags;
+pub mod pngrender;
+mod raster;
+pub mod scene;
+pub mod svgrender;
+pub mod termrender;
+pub mod verify;
+
+use colors::{ColorScheme, Pattern, Sticker};
 
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
 
 pub mod svg {
-    pub use crate::path::Path;
-    pub use crate::tags::Tag;
+    pub use crate::element::{escape_attr, escape_text, Element, Node};
+    pub use crate::path::{parse_path, Command, Path, Segment};
 }
 
 pub struct RenderOpts {
@@ -17,6 +35,9 @@ pub struct RenderOpts {
     pub border_width: u32,
     pub gutter_size: u32,
     pub sticker_width: u32,
+    pub colors: ColorScheme,
+    /// Draw a pattern over each kind of sticker as well as coloring it.
+    pub patterns: bool,
 }
 
 impl RenderOpts {
@@ -26,50 +47,106 @@ impl RenderOpts {
             border_width: 2,
             gutter_size: cubie_size / 10,
             sticker_width: cubie_size / 5,
+            colors: ColorScheme::default(),
+            patterns: false,
         }
     }
 }
 
 pub mod rendering {
     use super::*;
-    use svg::{Path, Tag};
+    use crate::cube::Face;
+    use crate::scene::{Paint, Shape, Stroke};
 
-    pub fn render_big_square(specs: &RenderOpts) -> String {
+    /// The paint for a sticker: its color, with its pattern over it when patterns are turned on.
+    pub fn sticker_paint(sticker: Sticker, specs: &RenderOpts) -> Paint {
+        let color = specs.colors.sticker(sticker).to_string();
+        if specs.patterns && sticker.pattern() != Pattern::Solid {
+            Paint::Pattern {
+                color,
+                pattern: sticker.pattern(),
+                size: (specs.cubie_size / 5).max(4) as f64,
+            }
+        } else {
+            Paint::Color(color)
+        }
+    }
+
+    /// The square that the cubies of a top-down diagram sit on.
+    pub fn big_square(specs: &RenderOpts) -> Shape {
         /*
           border * 2 + gutter * 4 + cell * 3
         */
         let big_square_size = big_square_size(specs);
-        render_square(
+        square(
             specs.sticker_width + specs.gutter_size,
             specs.sticker_width + specs.gutter_size,
             big_square_size,
-            "black",
+            specs.colors.background.as_str().into(),
         )
     }
 
-    pub fn render_square(x: u32, y: u32, width: u32, fill: &str) -> String {
-        let path = Path::new()
-            .M(x as i32, y as i32)
-            .h(width as i32)
-            .v(width as i32)
-            .h(-(width as i32))
-            .v(-(width as i32));
-
-        let tag = Tag::new("path")
-            .attr("fill", fill)
-            .attr("border-width", "0")
-            .attr("d", path.output());
-
-        let mut str = tag.open();
-        str.push_str(&tag.close());
+    pub fn square(x: u32, y: u32, width: u32, fill: Paint) -> Shape {
+        Shape::Rect {
+            x: x as f64,
+            y: y as f64,
+            width: width as f64,
+            height: width as f64,
+            fill,
+            stroke: None,
+        }
+    }
 
-        str
+    /// The sticker on the `side` face of the U layer cubie at `idx`, in the margin around the
+    /// big square.
+    pub fn side_sticker(idx: u32, side: Face, fill: Paint, specs: &RenderOpts) -> Option<Shape> {
+        let far = big_square_size(specs) + specs.sticker_width + specs.gutter_size * 2;
+        let (x, y, width, height) = match side {
+            Face::B => (
+                row_or_col_start(idx % 3, specs),
+                0,
+                specs.cubie_size,
+                specs.sticker_width,
+            ),
+            Face::F => (
+                row_or_col_start(idx % 3, specs),
+                far,
+                specs.cubie_size,
+                specs.sticker_width,
+            ),
+            Face::L => (
+                0,
+                row_or_col_start(idx / 3, specs),
+                specs.sticker_width,
+                specs.cubie_size,
+            ),
+            Face::R => (
+                far,
+                row_or_col_start(idx / 3, specs),
+                specs.sticker_width,
+                specs.cubie_size,
+            ),
+            Face::U | Face::D => return None,
+        };
+        Some(Shape::Rect {
+            x: x as f64,
+            y: y as f64,
+            width: width as f64,
+            height: height as f64,
+            fill,
+            stroke: Some(Stroke::new(&specs.colors.border, 2.0)),
+        })
     }
 
     pub fn big_square_size(specs: &RenderOpts) -> u32 {
         specs.border_width * 2 + specs.gutter_size * 4 + specs.cubie_size * 3
     }
 
+    /// The width and height of a top-down diagram, with room for side stickers.
+    pub fn top_view_size(specs: &RenderOpts) -> u32 {
+        big_square_size(specs) + specs.gutter_size * 2 + specs.sticker_width * 2
+    }
+
     pub fn row_or_col_start(idx: u32, specs: &RenderOpts) -> u32 {
         specs.sticker_width
             + specs.gutter_size * (2 + idx)
@@ -0,0 +1,53 @@
+use crate::colors::Sticker;
+use crate::cube::Face;
+use crate::llspec::{last_layer_side_sticker_faces, LastLayer};
+use crate::ollrender::{small_squares, sticker_side, stickers};
+use crate::pllrender::arrow;
+use crate::pllspec::SIDE_STICKERS;
+use crate::rendering::{big_square, side_sticker, sticker_paint, top_view_size};
+use crate::scene::{Renderer, Scene, Shape};
+use crate::svgrender::SvgRenderer;
+use crate::RenderOpts;
+
+/// Draw the orientation diagram for the last layer with the permutation arrows on top.
+pub fn render(last_layer: &LastLayer, specs: &RenderOpts) -> String {
+    SvgRenderer::new().render(&scene(last_layer, specs))
+}
+
+pub fn scene(last_layer: &LastLayer, specs: &RenderOpts) -> Scene {
+    let size = top_view_size(specs) as f64;
+    let mut scene = Scene::new(size, size);
+
+    scene.push(big_square(specs));
+    scene.extend(small_squares(&last_layer.desc, specs));
+    scene.extend(stickers(&last_layer.desc, specs));
+    if last_layer.program.side_stickers.is_some() {
+        scene.extend(side_stickers(last_layer, specs));
+    }
+
+    for stmt in &last_layer.program.statements.statements {
+        scene.push(arrow(stmt, specs));
+    }
+
+    scene
+}
+
+/// Draw the side stickers asked for by the PLL half, leaving out any spot where the OLL half
+/// already shows a U sticker. A case that can't happen has them all drawn as unknown.
+fn side_stickers(last_layer: &LastLayer, specs: &RenderOpts) -> Vec<Shape> {
+    let faces = last_layer_side_sticker_faces(last_layer).unwrap_or([None; 12]);
+    let shows_u_sticker = |idx: u8, side: Face| {
+        let dir = last_layer.desc.get(idx as usize).copied();
+        dir.and_then(sticker_side) == Some(side)
+    };
+
+    faces
+        .iter()
+        .zip(SIDE_STICKERS.iter())
+        .filter(|(_, (idx, side))| !shows_u_sticker(*idx, *side))
+        .filter_map(|(face, (idx, side))| {
+            let sticker = face.map_or(Sticker::Mask, Sticker::Face);
+            side_sticker(*idx as u32, *side, sticker_paint(sticker, specs), specs)
+        })
+        .collect()
+}
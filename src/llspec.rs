@@ -0,0 +1,163 @@
+use crate::algspec::Algorithm;
+use crate::cube::{Cube, Face};
+use crate::diagnostics::{ParseError, ParseResult};
+use crate::ollspec::{desc_for_cube, parse_desc, twist_top_layer, validate_desc, Direction};
+use crate::pllspec::{
+    cube_for_program, parse_program, side_sticker_faces, side_sticker_faces_for_cube,
+    top_layer_permutation, validate_program, Program, SideStickers, SIDE_STICKERS,
+};
+use crate::Result;
+use std::fmt;
+
+/*
+    A last layer case combines an OLL description and a PLL program, separated by a slash:
+
+    last_layer => desc '/' program
+
+    For example, "LUR L=R LDR / 1>3>9>1" or "U=U === L=D / (UFR UBR)(UF UB)".
+*/
+
+/// The whole last layer: how each cubie is oriented and where it has to go. This is what
+/// COLL, ZBLL and 1LLL diagrams show.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LastLayer {
+    pub desc: Vec<Direction>,
+    pub program: Program,
+}
+
+impl fmt::Display for LastLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let desc = self
+            .desc
+            .chunks(3)
+            .map(|row| row.iter().map(|dir| dir.letter()).collect::<String>())
+            .collect::<Vec<_>>();
+        write!(f, "{} / {}", desc.join(" "), self.program)
+    }
+}
+
+pub fn parse_last_layer(input: &str) -> ParseResult<LastLayer> {
+    let slash = input.find('/').ok_or_else(|| {
+        ParseError::new("", "Missing the '/' between the OLL and PLL parts")
+            .expecting(["'/'"])
+            .locate(input)
+    })?;
+
+    // Each half is parsed on its own, so errors have to be moved back into the full input.
+    let (desc_input, program_input) = (&input[..slash], &input[slash + 1..]);
+    let desc = parse_desc(desc_input).map_err(|err| relocate(err, input, 0))?;
+    let program = parse_program(program_input).map_err(|err| relocate(err, input, slash + 1))?;
+
+    Ok(LastLayer { desc, program })
+}
+
+fn relocate(err: ParseError, input: &str, start: usize) -> ParseError {
+    let tail = &input[start + err.offset()..];
+    ParseError::new(tail, err.message())
+        .expecting(err.expected().to_vec())
+        .locate(input)
+}
+
+/// Check that both halves describe something that can happen on a real cube.
+pub fn validate_last_layer(last_layer: &LastLayer) -> Result<()> {
+    validate_desc(&last_layer.desc)?;
+    validate_program(&last_layer.program)?;
+    Ok(())
+}
+
+/// Build a cube showing the whole case: the U layer cubies are moved as the arrows say and
+/// then twisted as the description says. Cubies whose direction is unknown are left untwisted.
+pub fn cube_for_last_layer(last_layer: &LastLayer) -> Result<Cube> {
+    let mut cube = cube_for_program(&last_layer.program)?;
+    twist_top_layer(&mut cube, &last_layer.desc)?;
+    Ok(cube)
+}
+
+/// The faces whose colors the side stickers show, like `pllspec::side_sticker_faces`. The
+/// orientation matters here too, so `SideStickers::Auto` reads them from
+/// `cube_for_last_layer`, and the stickers of cubies whose direction is unknown are unknown.
+pub fn last_layer_side_sticker_faces(last_layer: &LastLayer) -> Result<[Option<Face>; 12]> {
+    if last_layer.program.side_stickers != Some(SideStickers::Auto) {
+        return side_sticker_faces(&last_layer.program);
+    }
+
+    let mut faces = side_sticker_faces_for_cube(&cube_for_last_layer(last_layer)?);
+    for (face, (idx, _)) in faces.iter_mut().zip(SIDE_STICKERS.iter()) {
+        if last_layer.desc.get(*idx as usize) == Some(&Direction::Empty) {
+            *face = None;
+        }
+    }
+    Ok(faces)
+}
+
+/// Compute the last layer case that `alg` solves by applying its inverse to a solved cube.
+pub fn last_layer_for_algorithm(alg: &Algorithm) -> Result<LastLayer> {
+    let mut cube = Cube::solved();
+    cube.apply_all(&alg.expand_inverse());
+    last_layer_for_cube(&cube)
+}
+
+/// Describe the U layer of a cube whose first two layers are solved.
+pub fn last_layer_for_cube(cube: &Cube) -> Result<LastLayer> {
+    Ok(LastLayer {
+        desc: desc_for_cube(cube)?,
+        program: top_layer_permutation(cube)?.to_program(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_point_into_the_full_input() {
+        let err = parse_last_layer("=== === === / 1>é ").unwrap_err();
+        assert_eq!(err.offset(), 16);
+        assert!(err
+            .to_string()
+            .ends_with("\n  === === === / 1>é \n                  ^"));
+
+        let err = parse_last_layer("=== =é= === / 1>3").unwrap_err();
+        assert_eq!(err.offset(), 5);
+
+        let err = parse_last_layer("=== === ===  1>3").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Missing the '/' between the OLL and PLL parts"
+        );
+    }
+
+    fn side_stickers(input: &str) -> String {
+        let last_layer = parse_last_layer(input).unwrap();
+        last_layer_side_sticker_faces(&last_layer)
+            .unwrap()
+            .iter()
+            .map(|face| face.map_or('.', Face::letter))
+            .collect()
+    }
+
+    #[test]
+    fn auto_side_stickers_follow_twisted_corners() {
+        // Read in the order of SIDE_STICKERS: the B row, R column, F row and L column. Where a
+        // U sticker points sideways, the sticker that was there has moved around the corner.
+        // A cubie whose direction is unknown has unknown side stickers.
+        assert_eq!(side_stickers("U.R === ==D / {auto}"), "U.RURFFFUBLL");
+        assert_eq!(side_stickers("=== === === / {auto}"), "BBBRRRFFFLLL");
+
+        // With every cubie oriented, they come from the arrows alone.
+        let program = parse_program("1<>3 2<>8 {auto}").unwrap();
+        let last_layer = parse_last_layer("=== === === / 1<>3 2<>8 {auto}").unwrap();
+        assert_eq!(
+            last_layer_side_sticker_faces(&last_layer).unwrap(),
+            side_sticker_faces(&program).unwrap()
+        );
+
+        // A cubie whose direction is unknown has unknown side stickers.
+        assert_eq!(side_stickers("U.R === ==D / {auto}"), "U.RURFFFUBLL");
+        assert_eq!(side_stickers(".=R === ==D / {auto}"), ".BRURFFFU.LL");
+        assert_eq!(
+            side_stickers("U=R === ==D / {FRB ... ... ...}"),
+            "FRB........."
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::colors::Sticker;
+use crate::cube::{Cube, Face};
+use crate::rendering::{big_square_size, square, sticker_paint};
+use crate::scene::{Renderer, Scene, Shape};
+use crate::svgrender::SvgRenderer;
+use crate::RenderOpts;
+
+/// Where each face goes on the net, in faces across and down:
+///
+/// ```text
+///      U
+///    L F R B
+///      D
+/// ```
+fn face_position(face: Face) -> (u32, u32) {
+    match face {
+        Face::U => (1, 0),
+        Face::L => (0, 1),
+        Face::F => (1, 1),
+        Face::R => (2, 1),
+        Face::B => (3, 1),
+        Face::D => (1, 2),
+    }
+}
+
+/// Draw all six faces of the cube unfolded into a cross, like a scramble image.
+pub fn render(cube: &Cube, specs: &RenderOpts) -> String {
+    SvgRenderer::new().render(&scene(cube, specs))
+}
+
+pub fn scene(cube: &Cube, specs: &RenderOpts) -> Scene {
+    let face_size = big_square_size(specs);
+    let width = face_size * 4 + specs.gutter_size * 5;
+    let height = face_size * 3 + specs.gutter_size * 4;
+
+    let mut scene = Scene::new(width as f64, height as f64);
+
+    for face in Face::ALL.iter() {
+        let (across, down) = face_position(*face);
+        let x = specs.gutter_size + (face_size + specs.gutter_size) * across;
+        let y = specs.gutter_size + (face_size + specs.gutter_size) * down;
+        scene.extend(face_shapes(cube, *face, x, y, specs));
+    }
+
+    scene
+}
+
+fn face_shapes(cube: &Cube, face: Face, x: u32, y: u32, specs: &RenderOpts) -> Vec<Shape> {
+    let mut result = vec![square(
+        x,
+        y,
+        big_square_size(specs),
+        specs.colors.background.as_str().into(),
+    )];
+
+    for (idx, facelet) in cube.face(face).iter().enumerate() {
+        let idx = idx as u32;
+        result.push(square(
+            x + sticker_start(idx % 3, specs),
+            y + sticker_start(idx / 3, specs),
+            specs.cubie_size,
+            sticker_paint(Sticker::Face(*facelet), specs),
+        ));
+    }
+
+    result
+}
+
+/// The distance from the edge of a face to the start of the sticker in row or column `idx`.
+fn sticker_start(idx: u32, specs: &RenderOpts) -> u32 {
+    specs.border_width + specs.gutter_size * (idx + 1) + specs.cubie_size * idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{Amount, Layer, Move};
+
+    #[test]
+    fn faces_are_laid_out_in_a_cross() {
+        let specs = RenderOpts::with_cubie_size(30);
+        let scene = scene(&Cube::solved(), &specs);
+        // Faces are 106 pixels across, with 3 pixel gutters between them.
+        assert_eq!((scene.width, scene.height), (439.0, 330.0));
+
+        let corners = Face::ALL
+            .iter()
+            .map(|face| match &scene.shapes[face.index() * 10] {
+                Shape::Rect { x, y, fill, .. } => {
+                    assert_eq!(fill.color(), specs.colors.background);
+                    (*face, *x, *y)
+                }
+                shape => panic!("expected a rect, got {:?}", shape),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            corners,
+            vec![
+                (Face::U, 112.0, 3.0),
+                (Face::R, 221.0, 112.0),
+                (Face::F, 112.0, 112.0),
+                (Face::D, 112.0, 221.0),
+                (Face::L, 3.0, 112.0),
+                (Face::B, 330.0, 112.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn stickers_follow_the_cube() {
+        let specs = RenderOpts::with_cubie_size(30);
+        let mut cube = Cube::solved();
+        cube.apply(Move::new(Layer::Face(Face::R), Amount::Clockwise));
+        let scene = scene(&cube, &specs);
+
+        for face in Face::ALL.iter() {
+            let stickers = &scene.shapes[face.index() * 10 + 1..face.index() * 10 + 10];
+            for (shape, facelet) in stickers.iter().zip(cube.face(*face).iter()) {
+                match shape {
+                    Shape::Rect { fill, width, .. } => {
+                        assert_eq!(fill.color(), specs.colors.face(*facelet));
+                        assert_eq!(*width, 30.0);
+                    }
+                    shape => panic!("expected a rect, got {:?}", shape),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use crate::cube::{Move, AUF_ORDER};
+use crate::ollspec::{parse_desc, rotate_desc, Direction};
+use once_cell::sync::Lazy;
+use std::fmt;
+
+/// One of the 57 OLL cases, oriented the way it is usually shown on algorithm sheets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OllCase {
+    pub number: u8,
+    pub group: &'static str,
+    /// The case's nickname, for the few cases that have a widely used one.
+    pub name: Option<&'static str>,
+    pub desc: Vec<Direction>,
+    pub algorithms: &'static [&'static str],
+}
+
+type CaseData = (
+    u8,
+    &'static str,
+    Option<&'static str>,
+    &'static str,
+    &'static [&'static str],
+);
+
+/// Number, group, name, `parse_desc` spec and reference algorithms for every case. The specs
+/// were generated from the first algorithm with `ollspec::desc_for_algorithm`.
+static CASE_DATA: [CaseData; 57] = [
+    (
+        1,
+        "Dot",
+        None,
+        "LUR L=R LDR",
+        &["R U2 R2 F R F' U2 R' F R F'"],
+    ),
+    (
+        2,
+        "Dot",
+        None,
+        "LUU L=R LDD",
+        &["F R U R' U' F' f R U R' U' f'"],
+    ),
+    (
+        3,
+        "Dot",
+        None,
+        "UUR L=R LD=",
+        &["f R U R' U' f' U' F R U R' U' F'"],
+    ),
+    (
+        4,
+        "Dot",
+        None,
+        "LU= L=R DDR",
+        &["f R U R' U' f' U F R U R' U' F'"],
+    ),
+    (5, "Square", None, "UUR L== L==", &["r' U2 R U R' U r"]),
+    (6, "Square", None, "L== L== DDR", &["r U2 R' U' R U' r'"]),
+    (7, "Lightning", None, "U=R ==R =DD", &["r U R' U R U2 r'"]),
+    (8, "Lightning", None, "L=U L== DD=", &["l' U' L U' L' U2 l"]),
+    (
+        9,
+        "Fish",
+        None,
+        "L=U ==R DD=",
+        &["R U R' U' R' F R2 U R' U' F'"],
+    ),
+    (
+        10,
+        "Fish",
+        None,
+        "UU= ==R L=D",
+        &["R U R' U R' F R F' R U2 R'"],
+    ),
+    (
+        11,
+        "Lightning",
+        None,
+        "U== ==R LDD",
+        &["r U R' U R' F R F' R U2 r'"],
+    ),
+    (
+        12,
+        "Lightning",
+        None,
+        "==U L== DDR",
+        &["M' R' U' R U' R' U2 R U' M"],
+    ),
+    (
+        13,
+        "Knight Move",
+        None,
+        "UUR === =DD",
+        &["F U R U' R2 F' R U R U' R'"],
+    ),
+    (
+        14,
+        "Knight Move",
+        None,
+        "LUU === DD=",
+        &["R' F R U R' F' R F U' F'"],
+    ),
+    (
+        15,
+        "Knight Move",
+        None,
+        "=UR === LDD",
+        &["l' U' l L' U' L U l' U l"],
+    ),
+    (
+        16,
+        "Knight Move",
+        None,
+        "LU= === DDR",
+        &["r U r' R U R' U' r U' r'"],
+    ),
+    (
+        17,
+        "Dot",
+        None,
+        "=UU L=R LD=",
+        &["R U R' U R' F R F' U2 R' F R F'"],
+    ),
+    (
+        18,
+        "Dot",
+        None,
+        "=U= L=R DDD",
+        &["r U R' U R U2 r2 U' R U' R' U2 r"],
+    ),
+    (
+        19,
+        "Dot",
+        None,
+        "=U= L=R LDR",
+        &["r' R U R U R' U' M' R' F R F'"],
+    ),
+    (
+        20,
+        "Dot",
+        None,
+        "=U= L=R =D=",
+        &["r U R' U' M2 U R U' R' U' M'"],
+    ),
+    (
+        21,
+        "OCLL",
+        Some("H"),
+        "U=U === D=D",
+        &[
+            "R U2 R' U' R U R' U' R U' R'",
+            "R U R' U R U' R' U R U2 R'",
+            "F R U R' U' R U R' U' R U R' U' F'",
+        ],
+    ),
+    (
+        22,
+        "OCLL",
+        Some("Pi"),
+        "L=U === L=D",
+        &["R U2 R2 U' R2 U' R2 U2 R", "R' U2 R2 U R2 U R2 U2 R'"],
+    ),
+    (
+        23,
+        "OCLL",
+        Some("Headlights"),
+        "U=U === ===",
+        &["R2 D' R U2 R' D R U2 R", "R2 D R' U2 R D' R' U2 R'"],
+    ),
+    (
+        24,
+        "OCLL",
+        Some("Chameleon"),
+        "U== === D==",
+        &["r U R' U' r' F R F'"],
+    ),
+    (
+        25,
+        "OCLL",
+        Some("Bowtie"),
+        "L== === ==D",
+        &["F' r U R' U' r' F R", "R' F R B' R' F' R B"],
+    ),
+    (
+        26,
+        "OCLL",
+        Some("Antisune"),
+        "L== === D=R",
+        &["R U2 R' U' R U' R'", "R' U' R U' R' U2 R"],
+    ),
+    (
+        27,
+        "OCLL",
+        Some("Sune"),
+        "U=R === ==D",
+        &["R U R' U R U2 R'", "y' R' U2 R U R' U R"],
+    ),
+    (
+        28,
+        "Corners Oriented",
+        None,
+        "=== ==R =D=",
+        &["r U R' U' r' R U R U' R'", "M' U M U2 M' U M"],
+    ),
+    (
+        29,
+        "Awkward",
+        None,
+        "U== ==R DD=",
+        &["R U R' U' R U' R' F' U' F R U R'"],
+    ),
+    (
+        30,
+        "Awkward",
+        None,
+        "L=R ==R =D=",
+        &["F R' F R2 U' R' U' R U R' F2"],
+    ),
+    (
+        31,
+        "P Shape",
+        None,
+        "U== L== DD=",
+        &["R' U' F U R U' R' F' R"],
+    ),
+    (
+        32,
+        "P Shape",
+        None,
+        "==U ==R =DD",
+        &["L U F' U' L' U L F L'"],
+    ),
+    (33, "T Shape", None, "UU= === DD=", &["R U R' U' R' F R F'"]),
+    (
+        34,
+        "C Shape",
+        None,
+        "LUR === =D=",
+        &["R U R2 U' R' F R U R U' F'"],
+    ),
+    (35, "Fish", None, "=UR L== D==", &["R U2 R2 F R F' R U2 R'"]),
+    (
+        36,
+        "W Shape",
+        None,
+        "==U L== LD=",
+        &["L' U' L U' L' U L U L F' L' F"],
+    ),
+    (
+        37,
+        "Fish",
+        None,
+        "==R ==R DD=",
+        &["F R' F' R U R U' R'", "F R U' R' U' R U R' F'"],
+    ),
+    (
+        38,
+        "W Shape",
+        None,
+        "U== ==R =DR",
+        &["R U R' U R U' R' U' R' F R F'"],
+    ),
+    (
+        39,
+        "Lightning",
+        None,
+        "UU= === =DR",
+        &["L F' L' U' L U F U' L'"],
+    ),
+    (
+        40,
+        "Lightning",
+        None,
+        "=UU === LD=",
+        &["R' F R U R' U' F' U R"],
+    ),
+    (
+        41,
+        "Awkward",
+        None,
+        "U=U ==R =D=",
+        &["R U R' U R U2 R' F R U R' U' F'"],
+    ),
+    (
+        42,
+        "Awkward",
+        None,
+        "=U= ==R D=D",
+        &["R' U' R U' R' U2 R F R U R' U' F'"],
+    ),
+    (
+        43,
+        "P Shape",
+        None,
+        "L== L== LD=",
+        &["F' U' L' U L F", "R' U' F' U F R"],
+    ),
+    (
+        44,
+        "P Shape",
+        None,
+        "==R ==R =DR",
+        &["F U R U' R' F'", "f R U R' U' f'"],
+    ),
+    (45, "T Shape", None, "LU= === LD=", &["F R U R' U' F'"]),
+    (46, "C Shape", None, "==R L=R ==R", &["R' U' R' F R F' U R"]),
+    (
+        47,
+        "L Shape",
+        None,
+        "U=R L== DDR",
+        &["R' U' R' F R F' R' F R F' U R"],
+    ),
+    (
+        48,
+        "L Shape",
+        None,
+        "L=U ==R LDD",
+        &["F R U R' U' R U R' U' F'"],
+    ),
+    (
+        49,
+        "L Shape",
+        None,
+        "L=U L== LDD",
+        &["r U' r2 U r2 U r2 U' r"],
+    ),
+    (
+        50,
+        "L Shape",
+        None,
+        "LUU L== L=D",
+        &["r' U r2 U' r2 U' r2 U r'"],
+    ),
+    (
+        51,
+        "I Shape",
+        None,
+        "UUR === DDR",
+        &["F U R U' R' U R U' R' F'", "f R U R' U' R U R' U' f'"],
+    ),
+    (
+        52,
+        "I Shape",
+        None,
+        "U=R L=R D=R",
+        &["R U R' U R U' B U' B' R'"],
+    ),
+    (
+        53,
+        "L Shape",
+        None,
+        "LUR L== L=R",
+        &["r' U' R U' R' U R U' R' U2 r"],
+    ),
+    (
+        54,
+        "L Shape",
+        None,
+        "L=R L== LDR",
+        &["r U R' U R U' R' U R U2 r'"],
+    ),
+    (
+        55,
+        "I Shape",
+        None,
+        "UUU === DDD",
+        &["R' F R U R U' R2 F' R2 U' R' U R U R'"],
+    ),
+    (
+        56,
+        "I Shape",
+        None,
+        "LUR === LDR",
+        &["r' U' r U' R' U R U' R' U R r' U r"],
+    ),
+    (
+        57,
+        "Corners Oriented",
+        None,
+        "=U= === =D=",
+        &["R U R' U' M' U R U' r'", "R U R' U' r R' U R U' r'"],
+    ),
+];
+
+static CASES: Lazy<Vec<OllCase>> = Lazy::new(|| {
+    CASE_DATA
+        .iter()
+        .map(|(number, group, name, spec, algorithms)| OllCase {
+            number: *number,
+            group,
+            name: *name,
+            // unwrap: the specs above are known to be good.
+            desc: parse_desc(spec).unwrap(),
+            algorithms,
+        })
+        .collect()
+});
+
+/// All of the OLL cases, in numerical order.
+pub fn all_cases() -> &'static [OllCase] {
+    &CASES
+}
+
+pub fn case_by_number(number: u8) -> Option<&'static OllCase> {
+    CASES.iter().find(|case| case.number == number)
+}
+
+/// A case found by `identify`, along with the U turn needed before the case's algorithms apply.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OllMatch {
+    pub case: &'static OllCase,
+    pub pre_auf: Option<Move>,
+}
+
+impl fmt::Display for OllMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OLL {} ({}", self.case.number, self.case.group)?;
+        if let Some(name) = self.case.name {
+            write!(f, ", {}", name)?;
+        }
+        write!(f, ")")?;
+        if let Some(mv) = self.pre_auf {
+            write!(f, ", pre-AUF {}", mv)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the case that `desc` shows, trying all four AUFs. Returns None if `desc` isn't one of
+/// the 57 cases (including the solved U layer, which isn't a case).
+pub fn identify(desc: &[Direction]) -> Option<OllMatch> {
+    if desc.len() != 9 {
+        return None;
+    }
+
+    for turns in AUF_ORDER.iter() {
+        // Turning the case by `turns` gives `desc`, so undo that turn first.
+        let rotated = rotate_desc(desc, 4 - turns);
+        if let Some(case) = CASES.iter().find(|case| case.desc == rotated) {
+            return Some(OllMatch {
+                case,
+                pre_auf: Move::auf(4 - turns),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algspec::parse_algorithm;
+    use crate::ollspec::desc_for_algorithm;
+
+    #[test]
+    fn specs_match_the_first_algorithm() {
+        assert_eq!(all_cases().len(), 57);
+        for case in all_cases() {
+            let alg = parse_algorithm(case.algorithms[0]).unwrap();
+            assert_eq!(
+                desc_for_algorithm(&alg).unwrap(),
+                case.desc,
+                "OLL {}",
+                case.number
+            );
+        }
+    }
+
+    #[test]
+    fn numbers_are_in_order() {
+        for (idx, case) in all_cases().iter().enumerate() {
+            assert_eq!(case.number as usize, idx + 1);
+            assert_eq!(case_by_number(case.number), Some(case));
+        }
+        assert_eq!(case_by_number(0), None);
+        assert_eq!(case_by_number(58), None);
+    }
+
+    #[test]
+    fn identify_under_every_auf() {
+        let sune = case_by_number(27).unwrap();
+        for turns in 0..4 {
+            let found = identify(&rotate_desc(&sune.desc, turns)).unwrap();
+            assert_eq!(found.case.number, 27);
+            assert_eq!(found.pre_auf, Move::auf(4 - turns), "{} turns", turns);
+        }
+
+        let found = identify(&rotate_desc(&sune.desc, 1)).unwrap();
+        assert_eq!(found.to_string(), "OLL 27 (OCLL, Sune), pre-AUF U'");
+    }
+
+    #[test]
+    fn identify_rejects_non_cases() {
+        assert_eq!(identify(&parse_desc("=== === ===").unwrap()), None);
+        assert_eq!(identify(&parse_desc("U=R === ==").unwrap()), None);
+        assert_eq!(identify(&[Direction::Face; 10]), None);
+        assert_eq!(identify(&[]), None);
+    }
+
+    #[test]
+    fn rotate_leaves_partial_descs_alone() {
+        let desc = [Direction::Up; 10];
+        assert_eq!(rotate_desc(&desc, 1), desc);
+        assert_eq!(rotate_desc(&desc[..3], 1), &desc[..3]);
+    }
+}
@@ -1,31 +1,30 @@
+use crate::colors::Sticker;
+use crate::cube::Face;
 use crate::ollspec::Direction;
-use crate::rendering::{big_square_size, render_big_square, render_square, row_or_col_start};
-use crate::svg::{Path, Tag};
+use crate::rendering::{
+    big_square, row_or_col_start, side_sticker, square, sticker_paint, top_view_size,
+};
+use crate::scene::{Renderer, Scene, Shape};
+use crate::svgrender::SvgRenderer;
 use crate::RenderOpts;
 
 pub fn render(desc: &[Direction], specs: &RenderOpts) -> String {
-    let mut svg = String::new();
-
-    let size = big_square_size(specs) + specs.gutter_size * 2 + specs.sticker_width * 2;
-
-    let tag = Tag::new("svg")
-        .attr("xmlns", "http://www.w3.org/2000/svg")
-        .attr("height", &size.to_string())
-        .attr("width", &size.to_string());
-
-    svg.push_str(&tag.open());
+    SvgRenderer::new().render(&scene(desc, specs))
+}
 
-    svg.push_str(&render_big_square(&specs));
-    svg.push_str(&render_small_squares(desc, &specs));
-    svg.push_str(&render_stickers(desc, &specs));
+pub fn scene(desc: &[Direction], specs: &RenderOpts) -> Scene {
+    let size = top_view_size(specs) as f64;
+    let mut scene = Scene::new(size, size);
 
-    svg.push_str(&tag.close());
+    scene.push(big_square(specs));
+    scene.extend(small_squares(desc, specs));
+    scene.extend(stickers(desc, specs));
 
-    svg
+    scene
 }
 
-fn render_small_squares(desc: &[Direction], specs: &RenderOpts) -> String {
-    let mut result = String::default();
+pub(crate) fn small_squares(desc: &[Direction], specs: &RenderOpts) -> Vec<Shape> {
+    let mut result = vec![];
     for idx in 0..9 {
         let row = idx / 3;
         let col = idx % 3;
@@ -33,91 +32,39 @@ fn render_small_squares(desc: &[Direction], specs: &RenderOpts) -> String {
         let x = row_or_col_start(col, specs);
         let y = row_or_col_start(row, specs);
 
-        let color = color_for_direction(desc[idx as usize]);
-        result.push_str(&render_square(x, y, specs.cubie_size, color))
+        let dir = desc.get(idx as usize).copied().unwrap_or(Direction::Empty);
+        let fill = sticker_paint(sticker_for_direction(dir), specs);
+        result.push(square(x, y, specs.cubie_size, fill));
     }
     result
 }
 
-fn render_stickers(desc: &[Direction], specs: &RenderOpts) -> String {
-    let mut result = String::default();
-
-    for (idx, dir) in desc.iter().enumerate() {
-        match *dir {
-            Direction::Up => {
-                let x = row_or_col_start(idx as u32 % 3, specs);
-                result.push_str(&render_rect(
-                    x,
-                    0,
-                    specs.cubie_size,
-                    specs.sticker_width,
-                    "yellow",
-                ));
-            }
-            Direction::Left => {
-                let y = row_or_col_start(idx as u32 / 3, specs);
-                result.push_str(&render_rect(
-                    0,
-                    y,
-                    specs.sticker_width,
-                    specs.cubie_size,
-                    "yellow",
-                ));
-            }
-            Direction::Right => {
-                let x = big_square_size(specs) + specs.sticker_width + specs.gutter_size * 2;
-                let y = row_or_col_start(idx as u32 / 3, specs);
-                result.push_str(&render_rect(
-                    x,
-                    y,
-                    specs.sticker_width,
-                    specs.cubie_size,
-                    "yellow",
-                ));
-            }
-            Direction::Down => {
-                let x = row_or_col_start(idx as u32 % 3, specs);
-                let y = big_square_size(specs) + specs.sticker_width + specs.gutter_size * 2;
-                result.push_str(&render_rect(
-                    x,
-                    y,
-                    specs.cubie_size,
-                    specs.sticker_width,
-                    "yellow",
-                ));
-            }
-            _ => {}
-        }
-    }
-
-    result
+pub(crate) fn stickers(desc: &[Direction], specs: &RenderOpts) -> Vec<Shape> {
+    let top = sticker_paint(Sticker::Face(Face::U), specs);
+    desc.iter()
+        .enumerate()
+        .filter_map(|(idx, dir)| {
+            let side = sticker_side(*dir)?;
+            side_sticker(idx as u32, side, top.clone(), specs)
+        })
+        .collect()
 }
 
-fn render_rect(x: u32, y: u32, width: u32, height: u32, fill: &str) -> String {
-    let path = Path::new()
-        .M(x as i32, y as i32)
-        .h(width as i32)
-        .v(height as i32)
-        .h(-(width as i32))
-        .v(-(height as i32));
-
-    let tag = Tag::new("path")
-        .attr("fill", fill)
-        .attr("stroke", "black")
-        .attr("stroke-width", &2.to_string())
-        .attr("d", path.output());
-
-    let mut str = tag.open();
-    str.push_str(&tag.close());
-
-    str
+/// The side face that a U sticker pointing in `dir` lies on, if it isn't on top.
+pub(crate) fn sticker_side(dir: Direction) -> Option<Face> {
+    match dir {
+        Direction::Up => Some(Face::B),
+        Direction::Left => Some(Face::L),
+        Direction::Right => Some(Face::R),
+        Direction::Down => Some(Face::F),
+        _ => None,
+    }
 }
 
-fn color_for_direction(dir: Direction) -> &'static str {
-    use Direction::*;
+fn sticker_for_direction(dir: Direction) -> Sticker {
     match dir {
-        Face => "yellow",
-        Empty => "gray",
-        _ => "white",
+        Direction::Face => Sticker::Face(Face::U),
+        Direction::Empty => Sticker::Mask,
+        _ => Sticker::Unoriented,
     }
 }
@@ -1,6 +1,10 @@
+use crate::algspec::Algorithm;
+use crate::cube::{face_of_facelet, top_layer_facelets, Cube, Face, U_TURN};
+use crate::diagnostics::{ParseError, ParseResult};
 use crate::Result;
 use anyhow::anyhow;
 use once_cell::sync::Lazy;
+use std::fmt;
 use std::str::FromStr;
 
 /// For each of the nine positions on a cube face, list the legal positions for the sticker.
@@ -37,51 +41,384 @@ pub enum Direction {
     Empty,
 }
 
+impl Direction {
+    fn from_char(ch: char) -> Option<Direction> {
+        use Direction::*;
+
+        match ch {
+            'U' => Some(Up),
+            'D' => Some(Down),
+            'L' => Some(Left),
+            'R' => Some(Right),
+            '=' | 'F' => Some(Face),
+            '.' | 'E' | 'X' | 'x' => Some(Empty),
+            _ => None,
+        }
+    }
+
+    pub fn letter(self) -> char {
+        use Direction::*;
+
+        match self {
+            Up => 'U',
+            Down => 'D',
+            Left => 'L',
+            Right => 'R',
+            Face => '=',
+            Empty => '.',
+        }
+    }
+
+    /// The direction after a clockwise quarter turn of the U layer.
+    fn turned(self) -> Direction {
+        use Direction::*;
+        match self {
+            Up => Right,
+            Right => Down,
+            Down => Left,
+            Left => Up,
+            other => other,
+        }
+    }
+}
+
 impl FromStr for Direction {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        use Direction::*;
-
         match s.chars().next() {
             None => Err(anyhow!("Cannot parse Direction from empty string")),
-            Some(ch) => match ch {
-                'U' => Ok(Up),
-                'D' => Ok(Down),
-                'L' => Ok(Left),
-                'R' => Ok(Right),
-                '=' | 'F' => Ok(Face),
-                '.' | 'E' | 'X' | 'x' => Ok(Empty),
-                _ => Err(anyhow!(format!("Unknown char, '{}', for Direction", ch))),
-            },
+            Some(ch) => Direction::from_char(ch)
+                .ok_or_else(|| anyhow!(format!("Unknown char, '{}', for Direction", ch))),
         }
     }
 }
 
-pub fn parse_desc(input: &str) -> Result<Vec<Direction>> {
+pub fn parse_desc(input: &str) -> ParseResult<Vec<Direction>> {
     use Direction::*;
     let mut dirs = vec![];
-    let mut idx = 0;
-    for ch in input.chars() {
+    for (offset, ch) in input.char_indices() {
         if ch.is_whitespace() {
             continue;
         }
 
-        let direction = ch.to_string().parse::<Direction>().unwrap();
+        let tail = &input[offset..];
+        let error = |parsing: &str| ParseError::unexpected(tail, parsing).locate(input);
+        let cubie = CUBE.get(dirs.len()).ok_or_else(|| {
+            error("Direction").expecting(["end of input (a face has only 9 cubies)"])
+        })?;
+        let direction = Direction::from_char(ch).ok_or_else(|| {
+            error("Direction").expecting(["'U'", "'D'", "'L'", "'R'", "'='", "'.'"])
+        })?;
 
-        let cubie = CUBE.get(idx).unwrap();
         match direction {
-            Face => dirs.push(Face),
-            Empty => dirs.push(Empty),
+            Face | Empty => dirs.push(direction),
             _ => {
                 if cubie.contains(&direction) {
                     dirs.push(direction);
                 } else {
-                    panic!("XXXYYY");
+                    let legal = cubie
+                        .iter()
+                        .chain([Face, Empty].iter())
+                        .map(|dir| format!("'{}'", dir.letter()));
+                    return Err(error(&format!("cubie {}", dirs.len() + 1)).expecting(legal));
+                }
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// Something that makes a description impossible to reach on a real cube. Cubies are
+/// numbered 1-9, like in `pllspec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescProblem {
+    /// The description doesn't have exactly nine cells.
+    WrongSize(usize),
+    /// An odd number of edges are flipped. Lists the flipped edges.
+    EdgeFlip(Vec<u8>),
+    /// The corner twists don't cancel out. Lists the corners twisted clockwise and
+    /// counter-clockwise.
+    CornerTwist(Vec<u8>, Vec<u8>),
+}
+
+impl fmt::Display for DescProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DescProblem::WrongSize(size) => {
+                write!(f, "A face has 9 cubies, but the description has {}", size)
+            }
+            DescProblem::EdgeFlip(flipped) => write!(
+                f,
+                "An odd number of edges are flipped ({})",
+                list_cubies(flipped)
+            ),
+            DescProblem::CornerTwist(clockwise, counter_clockwise) => {
+                write!(f, "The corner twists don't cancel out (")?;
+                if !clockwise.is_empty() {
+                    write!(f, "clockwise: {}", list_cubies(clockwise))?;
+                }
+                if !clockwise.is_empty() && !counter_clockwise.is_empty() {
+                    write!(f, "; ")?;
                 }
+                if !counter_clockwise.is_empty() {
+                    write!(f, "counter-clockwise: {}", list_cubies(counter_clockwise))?;
+                }
+                write!(f, ")")
             }
         }
-        idx += 1;
     }
+}
+
+fn list_cubies(cubies: &[u8]) -> String {
+    let list = cubies
+        .iter()
+        .map(|cubie| cubie.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if cubies.len() == 1 {
+        format!("cubie {}", list)
+    } else {
+        format!("cubies {}", list)
+    }
+}
+
+/// The edge positions on the face.
+const EDGES: [usize; 4] = [1, 3, 5, 7];
+
+/// The corner positions on the face, with the directions their U sticker points when the
+/// corner is twisted clockwise and counter-clockwise.
+const CORNERS: [(usize, Direction, Direction); 4] = [
+    (0, Direction::Left, Direction::Up),
+    (2, Direction::Up, Direction::Right),
+    (6, Direction::Down, Direction::Left),
+    (8, Direction::Right, Direction::Down),
+];
+
+/// Find everything that makes `desc` unreachable. Parity can't be checked when some of the
+/// cubies involved are `Empty`, so those checks are skipped.
+pub fn desc_problems(desc: &[Direction]) -> Vec<DescProblem> {
+    if desc.len() != 9 {
+        return vec![DescProblem::WrongSize(desc.len())];
+    }
+
+    let mut problems = vec![];
+
+    if EDGES.iter().all(|idx| desc[*idx] != Direction::Empty) {
+        let flipped = EDGES
+            .iter()
+            .filter(|idx| desc[**idx] != Direction::Face)
+            .map(|idx| *idx as u8 + 1)
+            .collect::<Vec<_>>();
+        if flipped.len() % 2 != 0 {
+            problems.push(DescProblem::EdgeFlip(flipped));
+        }
+    }
+
+    if CORNERS
+        .iter()
+        .all(|(idx, _, _)| desc[*idx] != Direction::Empty)
+    {
+        let twisted = |twist: fn(&(usize, Direction, Direction)) -> Direction| {
+            CORNERS
+                .iter()
+                .filter(|corner| desc[corner.0] == twist(corner))
+                .map(|corner| corner.0 as u8 + 1)
+                .collect::<Vec<_>>()
+        };
+        let clockwise = twisted(|corner| corner.1);
+        let counter_clockwise = twisted(|corner| corner.2);
+        if (clockwise.len() + 2 * counter_clockwise.len()) % 3 != 0 {
+            problems.push(DescProblem::CornerTwist(clockwise, counter_clockwise));
+        }
+    }
+
+    problems
+}
+
+/// Check that `desc` shows a case that can be reached on a real cube.
+pub fn validate_desc(desc: &[Direction]) -> Result<()> {
+    let problems = desc_problems(desc);
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let problems = problems
+        .iter()
+        .map(|problem| problem.to_string())
+        .collect::<Vec<_>>();
+    Err(anyhow!("Impossible OLL case: {}", problems.join(". ")))
+}
+
+/// Turn a description as if the U layer were turned clockwise `turns` times. Only a
+/// description of the whole face can be turned, so any other is returned as it is.
+pub fn rotate_desc(desc: &[Direction], turns: u32) -> Vec<Direction> {
+    let mut result = desc.to_vec();
+    if desc.len() != U_TURN.len() {
+        return result;
+    }
+
+    for _ in 0..turns % 4 {
+        let previous = result.clone();
+        for (dir, dest) in previous.iter().zip(U_TURN.iter()) {
+            result[*dest as usize] = dir.turned();
+        }
+    }
+    result
+}
+
+/// Compute the orientation diagram for the case that `alg` solves by applying its inverse to
+/// a solved cube.
+pub fn desc_for_algorithm(alg: &Algorithm) -> Result<Vec<Direction>> {
+    let mut cube = Cube::solved();
+    cube.apply_all(&alg.expand_inverse());
+    desc_for_cube(&cube)
+}
+
+/// Describe the orientation of the U layer of a cube whose first two layers are solved.
+pub fn desc_for_cube(cube: &Cube) -> Result<Vec<Direction>> {
+    if !cube.is_first_two_layers_solved() {
+        return Err(anyhow!(
+            "Cannot describe the U layer of a cube with unsolved F2L"
+        ));
+    }
+
+    let top_color = cube.center(Face::U);
+    let dirs = (0..9)
+        .map(|idx| {
+            let facelet = top_layer_facelets(idx)
+                .into_iter()
+                .find(|facelet| cube.facelet(*facelet) == top_color);
+            match facelet.map(face_of_facelet) {
+                Some(Face::U) => Direction::Face,
+                Some(Face::B) => Direction::Up,
+                Some(Face::F) => Direction::Down,
+                Some(Face::L) => Direction::Left,
+                Some(Face::R) => Direction::Right,
+                _ => Direction::Empty,
+            }
+        })
+        .collect();
+
     Ok(dirs)
 }
+
+/// Build a cube showing `desc`: solved apart from U layer cubies twisted in place.
+pub fn cube_for_desc(desc: &[Direction]) -> Result<Cube> {
+    validate_desc(desc)?;
+    if let Some(idx) = (0..desc.len()).find(|idx| *idx != 4 && desc[*idx] == Direction::Empty) {
+        return Err(anyhow!(
+            "Cannot build a cube with an unknown sticker at position {}",
+            idx
+        ));
+    }
+
+    let mut cube = Cube::solved();
+    twist_top_layer(&mut cube, desc)?;
+    Ok(cube)
+}
+
+/// Twist each U layer cubie of `cube` in place so that the sticker on top points the way
+/// `desc` says. Cubies whose direction is unknown are left alone.
+pub fn twist_top_layer(cube: &mut Cube, desc: &[Direction]) -> Result<()> {
+    for (idx, dir) in desc.iter().enumerate() {
+        let face = match dir {
+            Direction::Face | Direction::Empty => continue,
+            Direction::Up => Face::B,
+            Direction::Down => Face::F,
+            Direction::Left => Face::L,
+            Direction::Right => Face::R,
+        };
+
+        let facelet = top_layer_facelets(idx)
+            .into_iter()
+            .find(|facelet| face_of_facelet(*facelet) == face)
+            .ok_or_else(|| anyhow!("Position {} cannot face {:?}", idx, dir))?;
+        cube.twist_top_layer_cubie(idx, facelet)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(input: &str) -> Vec<DescProblem> {
+        desc_problems(&parse_desc(input).unwrap())
+    }
+
+    #[test]
+    fn reachable_cases() {
+        // Sune, with three corners twisted counter-clockwise.
+        assert_eq!(problems("U=R === ==D"), []);
+        assert!(validate_desc(&parse_desc("U=R === ==D").unwrap()).is_ok());
+        // One corner each way.
+        assert_eq!(problems("L=R === ==="), []);
+        assert_eq!(problems("=U= L== ==="), []);
+        assert_eq!(problems("=== === ==="), []);
+    }
+
+    #[test]
+    fn flipped_edge() {
+        assert_eq!(problems("=U= === ==="), [DescProblem::EdgeFlip(vec![2])]);
+        assert_eq!(
+            problems("=U= L== =D="),
+            [DescProblem::EdgeFlip(vec![2, 4, 8])]
+        );
+        assert_eq!(
+            DescProblem::EdgeFlip(vec![2]).to_string(),
+            "An odd number of edges are flipped (cubie 2)"
+        );
+    }
+
+    #[test]
+    fn twisted_corners() {
+        assert_eq!(
+            problems("L== === ==="),
+            [DescProblem::CornerTwist(vec![1], vec![])]
+        );
+        assert_eq!(
+            problems("L=U === ==="),
+            [DescProblem::CornerTwist(vec![1, 3], vec![])]
+        );
+        assert_eq!(
+            problems("U=R === ==="),
+            [DescProblem::CornerTwist(vec![], vec![1, 3])]
+        );
+        assert_eq!(
+            DescProblem::CornerTwist(vec![1, 3], vec![]).to_string(),
+            "The corner twists don't cancel out (clockwise: cubies 1, 3)"
+        );
+        assert_eq!(
+            DescProblem::CornerTwist(vec![1], vec![3, 9]).to_string(),
+            "The corner twists don't cancel out (clockwise: cubie 1; counter-clockwise: cubies 3, 9)"
+        );
+    }
+
+    #[test]
+    fn wrong_size() {
+        assert_eq!(problems("=== === =="), [DescProblem::WrongSize(8)]);
+        assert_eq!(
+            DescProblem::WrongSize(8).to_string(),
+            "A face has 9 cubies, but the description has 8"
+        );
+    }
+
+    #[test]
+    fn unknown_cubies_skip_the_check() {
+        assert_eq!(problems("=U= === =.="), []);
+        assert_eq!(problems("L=. === ==="), []);
+        assert_eq!(problems("L=. L== ==="), [DescProblem::EdgeFlip(vec![4])]);
+    }
+
+    #[test]
+    fn validate_lists_every_problem() {
+        let err = validate_desc(&parse_desc("LU= === ===").unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Impossible OLL case: An odd number of edges are flipped (cubie 2). The corner \
+             twists don't cancel out (clockwise: cubie 1)"
+        );
+    }
+}
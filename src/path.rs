@@ -1,46 +1,661 @@
+use crate::diagnostics::{ParseError, ParseResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// An SVG path, built up one command at a time or parsed from path data.
+///
+/// The builder methods are named after the SVG commands. Uppercase commands take absolute
+/// coordinates and lowercase ones are relative to the end of the previous command.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Path {
-    path_string: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub command: Command,
+    /// Whether the coordinates are relative to the end of the previous segment.
+    pub relative: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    MoveTo {
+        x: f64,
+        y: f64,
+    },
+    LineTo {
+        x: f64,
+        y: f64,
+    },
+    Horizontal {
+        x: f64,
+    },
+    Vertical {
+        y: f64,
+    },
+    /// A cubic Bézier curve with control points (`x1`, `y1`) and (`x2`, `y2`).
+    Cubic {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        x: f64,
+        y: f64,
+    },
+    /// A cubic Bézier curve whose first control point mirrors the previous curve's second.
+    SmoothCubic {
+        x2: f64,
+        y2: f64,
+        x: f64,
+        y: f64,
+    },
+    Quadratic {
+        x1: f64,
+        y1: f64,
+        x: f64,
+        y: f64,
+    },
+    /// A quadratic Bézier curve whose control point mirrors the previous curve's.
+    SmoothQuadratic {
+        x: f64,
+        y: f64,
+    },
+    /// An elliptical arc, with radii `rx` and `ry` and the x axis turned `rotation` degrees.
+    /// The flags choose which of the four possible arcs is drawn.
+    Arc {
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x: f64,
+        y: f64,
+    },
+    Close,
+}
+
+impl Command {
+    /// The command's letter in absolute form.
+    pub fn letter(&self) -> char {
+        match self {
+            Command::MoveTo { .. } => 'M',
+            Command::LineTo { .. } => 'L',
+            Command::Horizontal { .. } => 'H',
+            Command::Vertical { .. } => 'V',
+            Command::Cubic { .. } => 'C',
+            Command::SmoothCubic { .. } => 'S',
+            Command::Quadratic { .. } => 'Q',
+            Command::SmoothQuadratic { .. } => 'T',
+            Command::Arc { .. } => 'A',
+            Command::Close => 'Z',
+        }
+    }
+
+    fn args(&self) -> Vec<f64> {
+        let flag = |set: bool| if set { 1.0 } else { 0.0 };
+        match *self {
+            Command::MoveTo { x, y } => vec![x, y],
+            Command::LineTo { x, y } => vec![x, y],
+            Command::Horizontal { x } => vec![x],
+            Command::Vertical { y } => vec![y],
+            Command::Cubic {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => vec![x1, y1, x2, y2, x, y],
+            Command::SmoothCubic { x2, y2, x, y } => vec![x2, y2, x, y],
+            Command::Quadratic { x1, y1, x, y } => vec![x1, y1, x, y],
+            Command::SmoothQuadratic { x, y } => vec![x, y],
+            Command::Arc {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            } => vec![rx, ry, rotation, flag(large_arc), flag(sweep), x, y],
+            Command::Close => vec![],
+        }
+    }
 }
 
 impl Path {
     pub fn new() -> Self {
-        Path {
-            path_string: Default::default(),
-        }
+        Default::default()
     }
 
-    pub fn output(&self) -> &str {
-        &self.path_string.trim()
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
     }
 
-    #[allow(non_snake_case)]
-    pub fn L(mut self, x: i32, y: i32) -> Self {
-        self.add(&format!("L {} {}", x, y));
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn push(&mut self, command: Command, relative: bool) {
+        self.segments.push(Segment { command, relative });
+    }
+
+    fn with(mut self, command: Command, relative: bool) -> Self {
+        self.push(command, relative);
         self
     }
 
     #[allow(non_snake_case)]
-    pub fn M(mut self, x: i32, y: i32) -> Self {
-        self.add(&format!("M {} {} ", x, y));
-        self
+    pub fn M(self, x: f64, y: f64) -> Self {
+        self.with(Command::MoveTo { x, y }, false)
     }
 
-    pub fn h(mut self, x: i32) -> Self {
-        self.add(&format!("h {} ", x));
-        self
+    pub fn m(self, x: f64, y: f64) -> Self {
+        self.with(Command::MoveTo { x, y }, true)
     }
 
-    pub fn v(mut self, y: i32) -> Self {
-        self.add(&format!("v {} ", y));
-        self
+    #[allow(non_snake_case)]
+    pub fn L(self, x: f64, y: f64) -> Self {
+        self.with(Command::LineTo { x, y }, false)
     }
 
-    pub fn z(mut self) -> Self {
-        self.add("z");
-        self
+    pub fn l(self, x: f64, y: f64) -> Self {
+        self.with(Command::LineTo { x, y }, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn H(self, x: f64) -> Self {
+        self.with(Command::Horizontal { x }, false)
+    }
+
+    pub fn h(self, x: f64) -> Self {
+        self.with(Command::Horizontal { x }, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn V(self, y: f64) -> Self {
+        self.with(Command::Vertical { y }, false)
+    }
+
+    pub fn v(self, y: f64) -> Self {
+        self.with(Command::Vertical { y }, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn C(self, x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64) -> Self {
+        self.with(
+            Command::Cubic {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            },
+            false,
+        )
+    }
+
+    pub fn c(self, x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64) -> Self {
+        self.with(
+            Command::Cubic {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            },
+            true,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    pub fn S(self, x2: f64, y2: f64, x: f64, y: f64) -> Self {
+        self.with(Command::SmoothCubic { x2, y2, x, y }, false)
+    }
+
+    pub fn s(self, x2: f64, y2: f64, x: f64, y: f64) -> Self {
+        self.with(Command::SmoothCubic { x2, y2, x, y }, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Q(self, x1: f64, y1: f64, x: f64, y: f64) -> Self {
+        self.with(Command::Quadratic { x1, y1, x, y }, false)
+    }
+
+    pub fn q(self, x1: f64, y1: f64, x: f64, y: f64) -> Self {
+        self.with(Command::Quadratic { x1, y1, x, y }, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn T(self, x: f64, y: f64) -> Self {
+        self.with(Command::SmoothQuadratic { x, y }, false)
+    }
+
+    pub fn t(self, x: f64, y: f64) -> Self {
+        self.with(Command::SmoothQuadratic { x, y }, true)
+    }
+
+    #[allow(non_snake_case, clippy::too_many_arguments)]
+    pub fn A(
+        self,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x: f64,
+        y: f64,
+    ) -> Self {
+        let arc = Command::Arc {
+            rx,
+            ry,
+            rotation,
+            large_arc,
+            sweep,
+            x,
+            y,
+        };
+        self.with(arc, false)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn a(
+        self,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x: f64,
+        y: f64,
+    ) -> Self {
+        let arc = Command::Arc {
+            rx,
+            ry,
+            rotation,
+            large_arc,
+            sweep,
+            x,
+            y,
+        };
+        self.with(arc, true)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Z(self) -> Self {
+        self.with(Command::Close, false)
+    }
+
+    pub fn z(self) -> Self {
+        self.with(Command::Close, true)
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = self.command.letter();
+        if self.relative {
+            write!(f, "{}", letter.to_ascii_lowercase())?;
+        } else {
+            write!(f, "{}", letter)?;
+        }
+        for arg in self.command.args() {
+            write!(f, " {}", format_number(arg))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = self
+            .segments
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}", segments.join(" "))
+    }
+}
+
+/// Numbers are written with at most three decimal places, which is far finer than a pixel, and
+/// without trailing zeros.
+fn format_number(number: f64) -> String {
+    let str = format!("{:.3}", number);
+    let str = str.trim_end_matches('0').trim_end_matches('.');
+    if str == "-0" {
+        "0".to_string()
+    } else {
+        str.to_string()
+    }
+}
+
+impl FromStr for Path {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<Self> {
+        parse_path(s)
+    }
+}
+
+/*
+ * Path data, as in the SVG `d` attribute:
+ *
+ *   path    = segment*
+ *   segment = command args (sep? args)*
+ *   command = one of MmLlHhVvCcSsQqTtAaZz
+ *   args    = the command's numbers, separated by whitespace and/or a comma
+ *
+ * A command's letter can be left out when it repeats, except that more coordinates after a
+ * move are lines. Arc flags are a single '0' or '1' and need no separator after them.
+ */
+pub fn parse_path(input: &str) -> ParseResult<Path> {
+    parse_complete_path(input).map_err(|err| err.locate(input))
+}
+
+fn parse_complete_path(input: &str) -> ParseResult<Path> {
+    let mut path = Path::new();
+
+    let mut tail = skip_separators(input);
+    let mut previous: Option<char> = None;
+    while !tail.is_empty() {
+        let (letter, rest) = match tail.chars().next() {
+            Some(ch) if is_command(ch) => (ch, &tail[1..]),
+            // A number with no command letter repeats the previous command.
+            Some(ch) => match previous {
+                _ if !starts_number(ch) => {
+                    return Err(ParseError::unexpected(tail, "Path").expecting(["a path command"]))
+                }
+                Some('Z') | Some('z') | None => {
+                    return Err(ParseError::unexpected(tail, "Path").expecting(["a path command"]))
+                }
+                Some('M') => ('L', tail),
+                Some('m') => ('l', tail),
+                Some(letter) => (letter, tail),
+            },
+            None => break,
+        };
+        if previous.is_none() && !matches!(letter, 'M' | 'm') {
+            return Err(
+                ParseError::new(tail, "A path must start with a move").expecting(["'M'", "'m'"])
+            );
+        }
+
+        let (command, rest) = parse_args(letter, rest)?;
+        path.push(command, letter.is_ascii_lowercase());
+        previous = Some(letter);
+        tail = skip_separators(rest);
+    }
+
+    Ok(path)
+}
+
+fn is_command(ch: char) -> bool {
+    "MmLlHhVvCcSsQqTtAaZz".contains(ch)
+}
+
+fn starts_number(ch: char) -> bool {
+    ch.is_ascii_digit() || ch == '-' || ch == '+' || ch == '.'
+}
+
+fn parse_args(letter: char, input: &str) -> ParseResult<(Command, &str)> {
+    let command = match letter.to_ascii_uppercase() {
+        'M' => {
+            let ([x, y], input) = parse_numbers(input)?;
+            (Command::MoveTo { x, y }, input)
+        }
+        'L' => {
+            let ([x, y], input) = parse_numbers(input)?;
+            (Command::LineTo { x, y }, input)
+        }
+        'H' => {
+            let ([x], input) = parse_numbers(input)?;
+            (Command::Horizontal { x }, input)
+        }
+        'V' => {
+            let ([y], input) = parse_numbers(input)?;
+            (Command::Vertical { y }, input)
+        }
+        'C' => {
+            let ([x1, y1, x2, y2, x, y], input) = parse_numbers(input)?;
+            let cubic = Command::Cubic {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            };
+            (cubic, input)
+        }
+        'S' => {
+            let ([x2, y2, x, y], input) = parse_numbers(input)?;
+            (Command::SmoothCubic { x2, y2, x, y }, input)
+        }
+        'Q' => {
+            let ([x1, y1, x, y], input) = parse_numbers(input)?;
+            (Command::Quadratic { x1, y1, x, y }, input)
+        }
+        'T' => {
+            let ([x, y], input) = parse_numbers(input)?;
+            (Command::SmoothQuadratic { x, y }, input)
+        }
+        'A' => {
+            let ([rx, ry, rotation], input) = parse_numbers(input)?;
+            let (large_arc, input) = parse_flag(skip_separators(input))?;
+            let (sweep, input) = parse_flag(skip_separators(input))?;
+            let ([x, y], input) = parse_numbers(input)?;
+            let arc = Command::Arc {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            };
+            (arc, input)
+        }
+        _ => (Command::Close, input),
+    };
+    Ok(command)
+}
+
+fn parse_numbers<const N: usize>(input: &str) -> ParseResult<([f64; N], &str)> {
+    let mut numbers = [0.0; N];
+    let mut tail = input;
+    for number in numbers.iter_mut() {
+        let (parsed, rest) = parse_number(skip_separators(tail))?;
+        *number = parsed;
+        tail = rest;
+    }
+    Ok((numbers, tail))
+}
+
+/// Parse a number like `-1.5e3` or `.5`. Signs and a second '.' start the next number, so
+/// `1-2` and `.5.5` are each two numbers.
+fn parse_number(input: &str) -> ParseResult<(f64, &str)> {
+    let bytes = input.as_bytes();
+    let mut end = 0;
+    if matches!(bytes.first(), Some(b'+') | Some(b'-')) {
+        end += 1;
+    }
+    let digits = |from: usize| {
+        from + bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let mantissa_start = end;
+    end = digits(end);
+    if bytes.get(end) == Some(&b'.') {
+        end = digits(end + 1);
+    }
+    if end == mantissa_start || &input[mantissa_start..end] == "." {
+        return Err(ParseError::unexpected(input, "Number").expecting(["a number"]));
+    }
+    if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'+') | Some(b'-')) {
+            exponent += 1;
+        }
+        let exponent_end = digits(exponent);
+        if exponent_end > exponent {
+            end = exponent_end;
+        }
+    }
+
+    // unwrap: the text checked above is always a valid float.
+    let number = input[..end].parse::<f64>().unwrap();
+    Ok((number, &input[end..]))
+}
+
+fn parse_flag(input: &str) -> ParseResult<(bool, &str)> {
+    match input.chars().next() {
+        Some('0') => Ok((false, &input[1..])),
+        Some('1') => Ok((true, &input[1..])),
+        _ => Err(ParseError::unexpected(input, "Arc").expecting(["'0'", "'1'"])),
+    }
+}
+
+fn skip_separators(input: &str) -> &str {
+    let input = input.trim_start();
+    input.strip_prefix(',').unwrap_or(input).trim_start()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> String {
+        parse_path(input).unwrap().to_string()
+    }
+
+    fn error(input: &str) -> (usize, String) {
+        let err = parse_path(input).unwrap_err();
+        (
+            err.offset(),
+            err.to_string().lines().next().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn every_command() {
+        assert_eq!(
+            parse("M1 2 L3 4 H5 V6 C1 2 3 4 5 6 S1 2 3 4 Q1 2 3 4 T5 6 A1 2 3 0 1 4 5 Z"),
+            "M 1 2 L 3 4 H 5 V 6 C 1 2 3 4 5 6 S 1 2 3 4 Q 1 2 3 4 T 5 6 A 1 2 3 0 1 4 5 Z"
+        );
+        assert_eq!(parse(""), "");
+        assert_eq!(parse("  M 0,0  "), "M 0 0");
+    }
+
+    #[test]
+    fn relative_and_absolute_commands() {
+        let path = parse_path("M 1 2 l 3 4 L 5 6 z").unwrap();
+        let relative = path
+            .segments()
+            .iter()
+            .map(|segment| segment.relative)
+            .collect::<Vec<_>>();
+        assert_eq!(relative, [false, true, false, true]);
+        assert_eq!(
+            path.segments()[1].command,
+            Command::LineTo { x: 3.0, y: 4.0 }
+        );
+        assert_eq!(parse("m 1 2 h 3 v 4 Z"), "m 1 2 h 3 v 4 Z");
+    }
+
+    #[test]
+    fn implicit_repeated_commands() {
+        assert_eq!(parse("M 1 2 3 4 5 6"), "M 1 2 L 3 4 L 5 6");
+        assert_eq!(parse("m 1 2 3 4"), "m 1 2 l 3 4");
+        assert_eq!(parse("M0 0 L 1 1, 2 2"), "M 0 0 L 1 1 L 2 2");
+        assert_eq!(parse("M0 0 h 1 2 3"), "M 0 0 h 1 h 2 h 3");
+        assert_eq!(
+            parse("M0 0 c 1 2 3 4 5 6 7 8 9 10 11 12"),
+            "M 0 0 c 1 2 3 4 5 6 c 7 8 9 10 11 12"
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse("M 1.5.5"), "M 1.5 0.5");
+        assert_eq!(parse("M 1.5.5.5.5"), "M 1.5 0.5 L 0.5 0.5");
+        assert_eq!(parse("M 1e-3 1E3"), "M 0.001 1000");
+        assert_eq!(parse("M 1e2 -2E-1"), "M 100 -0.2");
+        assert_eq!(parse("M1-2-3+4"), "M 1 -2 L -3 4");
+        assert_eq!(parse("M-.5-.5"), "M -0.5 -0.5");
+        assert_eq!(parse("M 0.0001 -0.0001"), "M 0 0");
+        assert_eq!(parse("M 1.2346 2."), "M 1.235 2");
+    }
+
+    #[test]
+    fn arcs() {
+        let path = parse_path("M0 0 a5 5 30 1012 3").unwrap();
+        assert_eq!(
+            path.segments()[1],
+            Segment {
+                command: Command::Arc {
+                    rx: 5.0,
+                    ry: 5.0,
+                    rotation: 30.0,
+                    large_arc: true,
+                    sweep: false,
+                    x: 12.0,
+                    y: 3.0,
+                },
+                relative: true,
+            }
+        );
+        assert_eq!(parse("M0 0 A5,5,0,0,1,10,10"), "M 0 0 A 5 5 0 0 1 10 10");
+        assert_eq!(
+            parse("M0 0 A 5 5 0 0 1 10 10 5 5 0 1 0 0 0"),
+            "M 0 0 A 5 5 0 0 1 10 10 A 5 5 0 1 0 0 0"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let input = "M 0 0 L 10 5 l -1.5 2 A 5 5 30 1 0 12 3 z";
+        assert_eq!(parse(input), input);
+        let path = input.parse::<Path>().unwrap();
+        assert_eq!(path.to_string().parse::<Path>().unwrap(), path);
     }
 
-    fn add(&mut self, str: &str) {
-        self.path_string.push_str(str);
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("L 1 2"),
+            (
+                0,
+                "A path must start with a move, expected one of 'M', 'm'".to_string()
+            )
+        );
+        assert_eq!(
+            error("M 1"),
+            (
+                3,
+                "Unexpected end of input while parsing Number, expected a number".to_string()
+            )
+        );
+        assert_eq!(
+            error("M 1 2 x"),
+            (
+                6,
+                "Unexpected 'x' while parsing Path, expected a path command".to_string()
+            )
+        );
+        assert_eq!(error("M 1 2 z 3 4").0, 8);
+        assert_eq!(
+            error("M 0 0 A 5 5 0 2 0 1 1"),
+            (
+                14,
+                "Unexpected '2' while parsing Arc, expected one of '0', '1'".to_string()
+            )
+        );
+        assert_eq!(error("M 1 .").0, 4);
+        assert_eq!(error("M 1 2 L é").0, 8);
     }
 }
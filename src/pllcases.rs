@@ -0,0 +1,262 @@
+use crate::cube::{Move, AUF_ORDER};
+use crate::pllspec::{parse_program, permutation_for_program, Permutation, Program};
+use crate::Result;
+use once_cell::sync::Lazy;
+use std::fmt;
+
+/// One of the 21 PLL cases, oriented the way it is usually shown on algorithm sheets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PllCase {
+    pub name: &'static str,
+    pub program: Program,
+    pub permutation: Permutation,
+    pub algorithms: &'static [&'static str],
+}
+
+type CaseData = (&'static str, &'static str, &'static [&'static str]);
+
+/// Name, `parse_program` spec and reference algorithms for every case. The specs were generated
+/// from the first algorithm with `pllspec::program_for_algorithm`, which is why some of the
+/// algorithms end with an AUF.
+static CASE_DATA: [CaseData; 21] = [
+    (
+        "Aa",
+        "1>3 3>9 9>1",
+        &[
+            "x R' U R' D2 R U' R' D2 R2 x'",
+            "x L2 D2 L' U' L D2 L' U L' x'",
+        ],
+    ),
+    (
+        "Ab",
+        "1>9 9>3 3>1",
+        &["x R2 D2 R U R' D2 R U' R x'", "x L U' L D2 L' U L D2 L2 x'"],
+    ),
+    (
+        "E",
+        "1<>7 3<>9",
+        &["x' R U' R' D R U R' D' R U R' D R U' R' D' x"],
+    ),
+    (
+        "F",
+        "2<>8 3<>9",
+        &["R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"],
+    ),
+    (
+        "Ga",
+        "1>3 3>7 7>1 2>4 4>6 6>2",
+        &["R2 U R' U R' U' R U' R2 U' D R' U R D' U"],
+    ),
+    (
+        "Gb",
+        "1>3 3>7 7>1 4>8 8>6 6>4",
+        &["R' U' R U D' R2 U R' U R U' R U' R2 D U"],
+    ),
+    (
+        "Gc",
+        "1>3 3>7 7>1 2>8 8>6 6>2",
+        &["R2 U' R U' R U R' U R2 U D' R U' R' D U"],
+    ),
+    (
+        "Gd",
+        "1>3 3>7 7>1 2>4 4>8 8>2",
+        &["R U R' U' D R2 U' R U' R' U R' U R2 D' U"],
+    ),
+    ("H", "2<>8 4<>6", &["M2 U M2 U2 M2 U M2"]),
+    (
+        "Ja",
+        "2<>6 3<>9",
+        &["x R2 F R F' R U2 r' U r U2 x'", "R' U L' U2 R U' R' U2 R L"],
+    ),
+    ("Jb", "3<>9 6<>8", &["R U R' F' R U R' U' R' F R2 U' R' U'"]),
+    (
+        "Na",
+        "3<>7 4<>6",
+        &["R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'"],
+    ),
+    (
+        "Nb",
+        "1<>9 4<>6",
+        &["R' U R U' R' F' U' F R U R' F R' F' R U' R"],
+    ),
+    (
+        "Ra",
+        "2<>4 3<>9",
+        &["R U' R' U' R U R D R' U' R D' R' U2 R' U'"],
+    ),
+    ("Rb", "3<>9 4<>8", &["R2 F R U R U' R' F' R U2 R' U2 R U"]),
+    ("T", "3<>9 4<>6", &["R U R' U' R' F R2 U' R' U' R U R' F'"]),
+    (
+        "Ua",
+        "4>8 8>6 6>4",
+        &["M2 U M U2 M' U M2", "R U' R U R U R U' R' U' R2"],
+    ),
+    (
+        "Ub",
+        "4>6 6>8 8>4",
+        &["M2 U' M U2 M' U' M2", "R2 U R U R' U' R' U' R' U R'"],
+    ),
+    (
+        "V",
+        "1<>9 2<>6",
+        &["R' U R' U' y R' F' R2 U' R' U R' F R F"],
+    ),
+    (
+        "Y",
+        "1<>9 2<>4",
+        &["F R U' R' U' R U R' F' R U R' U' R' F R F'"],
+    ),
+    ("Z", "2<>6 4<>8", &["M' U M2 U M2 U M' U2 M2 U'"]),
+];
+
+static CASES: Lazy<Vec<PllCase>> = Lazy::new(|| {
+    CASE_DATA
+        .iter()
+        .map(|(name, spec, algorithms)| {
+            // unwrap: the specs above are known to be good.
+            let program = parse_program(spec).unwrap();
+            let permutation = permutation_for_program(&program).unwrap();
+            PllCase {
+                name,
+                program,
+                permutation,
+                algorithms,
+            }
+        })
+        .collect()
+});
+
+/// All of the PLL cases, in alphabetical order.
+pub fn all_cases() -> &'static [PllCase] {
+    &CASES
+}
+
+/// Look up a case by name, ignoring case, so both "Ua" and "ua" work.
+pub fn case_by_name(name: &str) -> Option<&'static PllCase> {
+    CASES
+        .iter()
+        .find(|case| case.name.eq_ignore_ascii_case(name))
+}
+
+/// A case found by `identify`, along with the U turns needed before and after the case's
+/// algorithms to perform the identified permutation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PllMatch {
+    pub case: &'static PllCase,
+    pub pre_auf: Option<Move>,
+    pub post_auf: Option<Move>,
+}
+
+impl fmt::Display for PllMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.case.name)?;
+        if let Some(mv) = self.pre_auf {
+            write!(f, ", pre-AUF {}", mv)?;
+        }
+        if let Some(mv) = self.post_auf {
+            write!(f, ", post-AUF {}", mv)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the case that `program` shows. Returns Ok(None) if the arrows form a permutation that
+/// isn't one of the 21 cases (or is just an AUF).
+pub fn identify(program: &Program) -> Result<Option<PllMatch>> {
+    Ok(identify_permutation(&permutation_for_program(program)?))
+}
+
+pub fn identify_permutation(permutation: &Permutation) -> Option<PllMatch> {
+    // Try the fewest and smallest AUFs first.
+    let mut aufs = AUF_ORDER
+        .iter()
+        .flat_map(|pre| AUF_ORDER.iter().map(move |post| (*pre, *post)))
+        .collect::<Vec<_>>();
+    aufs.sort_by_key(|(pre, post)| (*pre != 0) as u32 + (*post != 0) as u32);
+
+    for (pre, post) in aufs {
+        let found = CASES.iter().find(|case| {
+            Permutation::u_turn(pre)
+                .then(&case.permutation)
+                .then(&Permutation::u_turn(post))
+                == *permutation
+        });
+        if let Some(case) = found {
+            return Some(PllMatch {
+                case,
+                pre_auf: Move::auf(pre),
+                post_auf: Move::auf(post),
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algspec::parse_algorithm;
+    use crate::pllspec::{permutation_for_algorithm, program_for_algorithm};
+
+    #[test]
+    fn specs_match_the_first_algorithm() {
+        assert_eq!(all_cases().len(), 21);
+        for case in all_cases() {
+            let alg = parse_algorithm(case.algorithms[0]).unwrap();
+            assert_eq!(
+                permutation_for_algorithm(&alg).unwrap(),
+                case.permutation,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                program_for_algorithm(&alg).unwrap(),
+                case.program,
+                "{}",
+                case.name
+            );
+        }
+    }
+
+    #[test]
+    fn names_ignore_case() {
+        assert_eq!(case_by_name("ua").unwrap().name, "Ua");
+        assert_eq!(case_by_name("T").unwrap().name, "T");
+        assert!(case_by_name("X").is_none());
+    }
+
+    #[test]
+    fn identify_every_case() {
+        for case in all_cases() {
+            let found = identify_permutation(&case.permutation).unwrap();
+            assert_eq!(found.case.name, case.name);
+            assert_eq!((found.pre_auf, found.post_auf), (None, None));
+        }
+        assert_eq!(identify_permutation(&Permutation::identity()), None);
+        assert_eq!(identify_permutation(&Permutation::u_turn(1)), None);
+    }
+
+    #[test]
+    fn identify_with_aufs() {
+        let t = case_by_name("T").unwrap();
+        let program = parse_program("1<>3 2<>8").unwrap();
+        let found = identify(&program).unwrap().unwrap();
+        assert_eq!(found.case.name, "T");
+        assert_eq!(found.pre_auf, Move::auf(1));
+        assert_eq!(found.post_auf, Move::auf(3));
+        assert_eq!(found.to_string(), "T, pre-AUF U, post-AUF U'");
+
+        // The pre-AUF is done before the case's permutation and the post-AUF after it.
+        let permutation = Permutation::u_turn(1)
+            .then(&t.permutation)
+            .then(&Permutation::u_turn(3));
+        assert_eq!(identify_permutation(&permutation), Some(found));
+
+        let found = identify_permutation(&t.permutation.then(&Permutation::u_turn(2))).unwrap();
+        assert_eq!(
+            (found.case.name, found.pre_auf, found.post_auf),
+            ("T", None, Move::auf(2))
+        );
+    }
+}
@@ -1,105 +1,81 @@
-use crate::pllspec::{Operator, Program, Statement};
-use crate::rendering::{big_square_size, render_big_square, render_square, row_or_col_start};
-use crate::svg::{Path, Tag};
+use crate::colors::Sticker;
+use crate::cube::Face;
+use crate::pllspec::{side_sticker_faces, Operator, Program, Statement, SIDE_STICKERS};
+use crate::rendering::{
+    big_square, row_or_col_start, side_sticker, square, sticker_paint, top_view_size,
+};
+use crate::scene::{Marker, Renderer, Scene, Shape, Stroke};
+use crate::svgrender::SvgRenderer;
 use crate::RenderOpts;
 
 pub fn render(program: &Program, specs: &RenderOpts) -> String {
-    let mut svg = String::default();
-
-    let size = big_square_size(specs) + specs.gutter_size * 2 + specs.sticker_width * 2;
-    let tag = Tag::new("svg")
-        .attr("xmlns", "http://www.w3.org/2000/svg")
-        .attr("height", &size.to_string())
-        .attr("width", &size.to_string());
+    SvgRenderer::new().render(&scene(program, specs))
+}
 
-    svg.push_str(&tag.open());
+pub fn scene(program: &Program, specs: &RenderOpts) -> Scene {
+    let size = top_view_size(specs) as f64;
+    let mut scene = Scene::new(size, size);
 
-    svg.push_str(&render_defs());
-    svg.push_str(&render_big_square(&specs));
-    svg.push_str(&render_small_squares(&specs));
+    scene.push(big_square(specs));
+    scene.extend(small_squares(specs));
+    if program.side_stickers.is_some() {
+        scene.extend(side_stickers(program, specs));
+    }
 
     for stmt in &program.statements.statements {
-        svg.push_str(&render_stmt(stmt, specs));
+        scene.push(arrow(stmt, specs));
     }
 
-    svg.push_str(&tag.close());
-
-    svg
-}
-
-fn render_defs() -> String {
-    let mut output = String::default();
-
-    let defs = Tag::new("defs");
-    let marker = Tag::new("marker")
-        .attr("id", "arrow")
-        .attr("viewBox", "0 0 10 10")
-        .attr("refX", "5")
-        .attr("refY", "5")
-        .attr("markerWidth", "3")
-        .attr("markerHeight", "3")
-        .attr("orient", "auto-start-reverse");
-
-    let path = Path::new().M(0, 0).L(10, 5).L(0, 10).z();
-    let path_tag = Tag::new("path")
-        .attr("d", path.output())
-        .attr("fill", "red");
-
-    output.push_str(&defs.open());
-    output.push_str(&marker.open());
-    output.push_str(&path_tag.open());
-    output.push_str(&path_tag.close());
-    output.push_str(&marker.close());
-    output.push_str(&defs.close());
-
-    output
+    scene
 }
 
-fn render_stmt(stmt: &Statement, specs: &RenderOpts) -> String {
-    let start_row = stmt.start.idx / 3;
-    let start_col = stmt.start.idx % 3;
-    let end_row = stmt.end.idx / 3;
-    let end_col = stmt.end.idx % 3;
-
-    let start_x = row_or_col_start(start_col as u32, specs) + specs.cubie_size / 2;
-    let start_y = row_or_col_start(start_row as u32, specs) + specs.cubie_size / 2;
-    let end_x = row_or_col_start(end_col as u32, specs) + specs.cubie_size / 2;
-    let end_y = row_or_col_start(end_row as u32, specs) + specs.cubie_size / 2;
-
-    let tag = Tag::new("line")
-        .attr("x1", &start_x.to_string())
-        .attr("y1", &start_y.to_string())
-        .attr("x2", &end_x.to_string())
-        .attr("y2", &end_y.to_string())
-        .attr("stroke-width", "4")
-        .attr("stroke", "red");
-
-    let tag = match stmt.op {
-        Operator::StartHead => tag.attr("marker-start", "url(#arrow)"),
-        Operator::EndHead => tag.attr("marker-end", "url(#arrow)"),
-        Operator::BothHead => tag
-            .attr("marker-start", "url(#arrow)")
-            .attr("marker-end", "url(#arrow)"),
+pub(crate) fn arrow(stmt: &Statement, specs: &RenderOpts) -> Shape {
+    let center = |idx: u8| {
+        let row = idx as u32 / 3;
+        let col = idx as u32 % 3;
+        (
+            (row_or_col_start(col, specs) + specs.cubie_size / 2) as f64,
+            (row_or_col_start(row, specs) + specs.cubie_size / 2) as f64,
+        )
     };
 
-    let mut output = String::default();
-
-    output.push_str(&tag.open());
-    output.push_str(&tag.close());
+    let (start, end) = match stmt.op {
+        Operator::StartHead => (Marker::Arrow, Marker::None),
+        Operator::EndHead => (Marker::None, Marker::Arrow),
+        Operator::BothHead => (Marker::Arrow, Marker::Arrow),
+    };
 
-    output
+    Shape::Line {
+        from: center(stmt.start.idx),
+        to: center(stmt.end.idx),
+        stroke: Stroke::new(&specs.colors.arrow, 4.0),
+        start,
+        end,
+    }
 }
 
-fn render_small_squares(specs: &RenderOpts) -> String {
-    let mut result = String::default();
-    for idx in 0..9 {
-        let row = idx / 3;
-        let col = idx % 3;
-
-        let x = row_or_col_start(col, specs);
-        let y = row_or_col_start(row, specs);
+fn small_squares(specs: &RenderOpts) -> Vec<Shape> {
+    let top = sticker_paint(Sticker::Face(Face::U), specs);
+    (0..9)
+        .map(|idx| {
+            let x = row_or_col_start(idx % 3, specs);
+            let y = row_or_col_start(idx / 3, specs);
+            square(x, y, specs.cubie_size, top.clone())
+        })
+        .collect()
+}
 
-        result.push_str(&render_square(x, y, specs.cubie_size, "yellow"))
-    }
-    result
+/// Draw the side stickers. A program that can't happen has no sensible side stickers, so they
+/// are all drawn as unknown.
+fn side_stickers(program: &Program, specs: &RenderOpts) -> Vec<Shape> {
+    let faces = side_sticker_faces(program).unwrap_or([None; 12]);
+
+    faces
+        .iter()
+        .zip(SIDE_STICKERS.iter())
+        .filter_map(|(face, (idx, side))| {
+            let sticker = face.map_or(Sticker::Mask, Sticker::Face);
+            side_sticker(*idx as u32, *side, sticker_paint(sticker, specs), specs)
+        })
+        .collect()
 }
@@ -1,28 +1,145 @@
+use crate::algspec::Algorithm;
+use crate::cube::{face_of_facelet, top_layer_facelets, Cube, Face, U_TURN};
+use crate::diagnostics::{ParseError, ParseResult};
 use crate::Result;
 use anyhow::anyhow;
+use std::fmt;
 
 /*
     Simple grammar:
 
-    image => statements
+    image => statements stickers?
     statements => statement statements
+               => cycle statements
                =>
-    statement => cubie op cubie
+    statement => cubie op cubie (op cubie)*    1>3>9>1 is short for 1>3 3>9 9>1
+    cycle => '(' cubie (','? cubie)* ')'       (1 3 9) is short for 1>3>9>1, (2 8) for 2<>8
     cubie => [1-9]
+          => position                          UFR, UB, ULB: U plus the faces around it, any order
     op => '<'
        => '>'
        => '<>'
+    stickers => '{' 'auto' '}'                 work the side stickers out from the arrows
+             => '{' sticker{12} '}'            B row, R column, F row, L column, in reading order
+    sticker => [FRBL.]                         the face whose color the sticker is; '.' is unknown
 */
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Program {
     pub statements: Statements,
+    pub side_stickers: Option<SideStickers>,
 }
 
-pub fn parse_program(input: &str) -> Result<Program> {
-    let (statements, _) = parse_statements(input)?;
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = self
+            .statements
+            .statements
+            .iter()
+            .map(|stmt| stmt.to_string())
+            .collect::<Vec<_>>();
+        if let Some(side_stickers) = &self.side_stickers {
+            parts.push(side_stickers.to_string());
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+pub fn parse_program(input: &str) -> ParseResult<Program> {
+    parse_complete_program(input).map_err(|err| err.locate(input))
+}
+
+fn parse_complete_program(input: &str) -> ParseResult<Program> {
+    let (statements, tail) = parse_statements(input)?;
+    let side_stickers = match tail.strip_prefix('{') {
+        Some(tail) => Some(parse_side_stickers(tail)?),
+        None => None,
+    };
+
+    Ok(Program {
+        statements,
+        side_stickers,
+    })
+}
+
+/// The 12 stickers on the sides of the U layer, in the order they are written in a spec: the
+/// back row and right column from back-left to front-right, then the front row and left
+/// column the same way. Each is the cubie it is on and the face it is on.
+pub const SIDE_STICKERS: [(u8, Face); 12] = [
+    (0, Face::B),
+    (1, Face::B),
+    (2, Face::B),
+    (2, Face::R),
+    (5, Face::R),
+    (8, Face::R),
+    (6, Face::F),
+    (7, Face::F),
+    (8, Face::F),
+    (0, Face::L),
+    (3, Face::L),
+    (6, Face::L),
+];
+
+/// The colors of the side stickers, given as the face each color belongs to, with `None` for
+/// stickers that aren't known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SideStickers {
+    Auto,
+    Explicit([Option<Face>; 12]),
+}
+
+impl fmt::Display for SideStickers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SideStickers::Auto => write!(f, "{{auto}}"),
+            SideStickers::Explicit(faces) => {
+                let sides = faces
+                    .chunks(3)
+                    .map(|side| {
+                        side.iter()
+                            .map(|face| face.map_or('.', Face::letter))
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{{{}}}", sides.join(" "))
+            }
+        }
+    }
+}
+
+/// Parse the side stickers after their opening brace.
+fn parse_side_stickers(input: &str) -> ParseResult<SideStickers> {
+    let input = input.trim_start();
+    let (side_stickers, tail) = if let Some(tail) = input.strip_prefix("auto") {
+        (SideStickers::Auto, tail)
+    } else {
+        let mut faces = [None; 12];
+        let mut tail = input;
+        for face in faces.iter_mut() {
+            tail = tail.trim_start();
+            *face = match tail.chars().next() {
+                Some('.') => None,
+                Some(ch @ ('F' | 'R' | 'B' | 'L')) => Face::from_letter(ch),
+                _ => {
+                    return Err(ParseError::unexpected(tail, "side stickers")
+                        .expecting(["'F'", "'R'", "'B'", "'L'", "'.'"]))
+                }
+            };
+            tail = &tail[1..];
+        }
+        (SideStickers::Explicit(faces), tail)
+    };
+
+    let tail = tail.trim_start();
+    let tail = tail.strip_prefix('}').ok_or_else(|| {
+        ParseError::unexpected(tail, "side stickers").expecting(["'}' after 12 stickers"])
+    })?;
+    if !tail.trim().is_empty() {
+        return Err(ParseError::unexpected(tail.trim_start(), "Program")
+            .expecting(["end of input after the side stickers"]));
+    }
 
-    Ok(Program { statements })
+    Ok(side_stickers)
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -30,17 +147,21 @@ pub struct Statements {
     pub statements: Vec<Statement>,
 }
 
-fn parse_statements(input: &str) -> Result<(Statements, &str)> {
+fn parse_statements(input: &str) -> ParseResult<(Statements, &str)> {
     let mut statements = vec![];
 
-    let mut tail = input;
-    while !tail.is_empty() {
-        let (statement, tail_) = parse_statement(tail)?;
-        tail = tail_;
-        statements.push(statement);
+    let mut tail = input.trim_start();
+    while !tail.is_empty() && !tail.starts_with('{') {
+        let (parsed, tail_) = if let Some(tail_) = tail.strip_prefix('(') {
+            parse_cycle(tail_)?
+        } else {
+            parse_statement(tail)?
+        };
+        tail = tail_.trim_start();
+        statements.extend(parsed);
     }
 
-    Ok((Statements { statements }, input))
+    Ok((Statements { statements }, tail))
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -50,15 +171,80 @@ pub struct Statement {
     pub op: Operator,
 }
 
-fn parse_statement(input: &str) -> Result<(Statement, &str)> {
-    let input = input.trim();
-    let (start, input) = parse_cubie(input)?;
-    let input = input.trim();
-    let (op, input) = parse_operator(input)?;
-    let input = input.trim();
-    let (end, input) = parse_cubie(input)?;
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Operator::StartHead => "<",
+            Operator::EndHead => ">",
+            Operator::BothHead => "<>",
+        };
+        write!(f, "{}{}{}", self.start.idx + 1, op, self.end.idx + 1)
+    }
+}
 
-    Ok((Statement { start, op, end }, input))
+/// Parse a statement, or a chain of them that share cubies, like `1>3>9>1`.
+fn parse_statement(input: &str) -> ParseResult<(Vec<Statement>, &str)> {
+    let input = input.trim_start();
+    let (mut start, mut input) = parse_cubie(input)?;
+
+    let mut statements = vec![];
+    loop {
+        let (op, tail) = parse_operator(input.trim_start())?;
+        let (end, tail) = parse_cubie(tail.trim_start())?;
+        statements.push(Statement { start, op, end });
+
+        start = end;
+        input = tail;
+        if !input.trim_start().starts_with(['<', '>']) {
+            break;
+        }
+    }
+
+    Ok((statements, input))
+}
+
+/// Parse a cycle in permutation notation after its opening paren, like `1 3 9)`.
+fn parse_cycle(input: &str) -> ParseResult<(Vec<Statement>, &str)> {
+    let mut cubies = vec![];
+
+    let mut tail = input.trim_start();
+    loop {
+        if let Some(tail) = tail.strip_prefix(')') {
+            if cubies.len() < 2 {
+                return Err(ParseError::new(input, "A cycle needs at least two cubies"));
+            }
+            return Ok((cycle_statements(&cubies), tail));
+        }
+
+        let (cubie, tail_) = parse_cubie(tail).map_err(|err| err.expecting(["')'"]))?;
+        cubies.push(cubie);
+        tail = tail_.trim_start();
+        if let Some(tail_) = tail.strip_prefix(',') {
+            tail = tail_.trim_start();
+        }
+    }
+}
+
+/// Arrows for a cycle where each cubie moves to the next one: a double-headed arrow for a
+/// swap, otherwise a closed chain of single-headed arrows.
+fn cycle_statements(cycle: &[Cubie]) -> Vec<Statement> {
+    if cycle.len() == 2 {
+        return vec![Statement {
+            start: cycle[0],
+            end: cycle[1],
+            op: Operator::BothHead,
+        }];
+    }
+
+    cycle
+        .iter()
+        .enumerate()
+        .map(|(idx, start)| Statement {
+            start: *start,
+            end: cycle[(idx + 1) % cycle.len()],
+            op: Operator::EndHead,
+        })
+        .collect()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -68,19 +254,16 @@ pub enum Operator {
     BothHead,
 }
 
-fn parse_operator(input: &str) -> Result<(Operator, &str)> {
-    let input = input.trim();
-    if input.starts_with("<>") {
-        Ok((Operator::BothHead, &input[2..]))
-    } else if input.starts_with("<") {
-        Ok((Operator::StartHead, &input[1..]))
-    } else if input.starts_with(">") {
-        Ok((Operator::EndHead, &input[1..]))
+fn parse_operator(input: &str) -> ParseResult<(Operator, &str)> {
+    let input = input.trim_start();
+    if let Some(tail) = input.strip_prefix("<>") {
+        Ok((Operator::BothHead, tail))
+    } else if let Some(tail) = input.strip_prefix('<') {
+        Ok((Operator::StartHead, tail))
+    } else if let Some(tail) = input.strip_prefix('>') {
+        Ok((Operator::EndHead, tail))
     } else {
-        Err(anyhow!(
-            "Unexpected string, {}, found while parsing Operator",
-            &input[..1]
-        ))
+        Err(ParseError::unexpected(input, "Operator").expecting(["'<'", "'>'", "'<>'"]))
     }
 }
 
@@ -89,16 +272,30 @@ pub struct Cubie {
     pub idx: u8, // 0-indexed, 0-8.
 }
 
-fn parse_cubie(input: &str) -> Result<(Cubie, &str)> {
-    let input = input.trim();
-    let cubie_index = input[0..1].parse::<u8>()?;
+impl Cubie {
+    pub fn is_center(&self) -> bool {
+        self.idx == 4
+    }
 
-    if cubie_index < 1 || cubie_index > 9 {
-        return Err(anyhow!(
-            "Out-of-range value, {}, found while parsing Cubie",
-            cubie_index
-        ));
+    pub fn is_corner(&self) -> bool {
+        self.idx.is_multiple_of(2) && !self.is_center()
+    }
+
+    pub fn is_edge(&self) -> bool {
+        !self.idx.is_multiple_of(2)
     }
+}
+
+fn parse_cubie(input: &str) -> ParseResult<(Cubie, &str)> {
+    let input = input.trim_start();
+    let cubie_index = match input.chars().next() {
+        Some(ch @ '1'..='9') => ch as u8 - b'0',
+        Some(ch) if ch.is_ascii_alphabetic() => return parse_position(input),
+        _ => {
+            return Err(ParseError::unexpected(input, "Cubie")
+                .expecting(["'1'-'9'", "a position like UFR"]))
+        }
+    };
 
     Ok((
         Cubie {
@@ -107,3 +304,509 @@ fn parse_cubie(input: &str) -> Result<(Cubie, &str)> {
         &input[1..],
     ))
 }
+
+/// Parse a cubie named by the faces it touches, like `UFR` or `BU`.
+fn parse_position(input: &str) -> ParseResult<(Cubie, &str)> {
+    let len = input
+        .find(|ch: char| !ch.is_ascii_alphabetic())
+        .unwrap_or(input.len());
+    let (name, tail) = input.split_at(len);
+
+    let error = |why: &str| {
+        ParseError::new(
+            input,
+            format!("'{}' is not a U layer position: {}", name, why),
+        )
+        .expecting(["a position like UFR"])
+    };
+
+    let mut faces = vec![];
+    for ch in name.chars() {
+        let face = Face::from_letter(ch.to_ascii_uppercase())
+            .ok_or_else(|| error(&format!("'{}' is not a face", ch)))?;
+        if faces.contains(&face) {
+            return Err(error(&format!("it names {} twice", face.letter())));
+        }
+        if faces.contains(&face.opposite()) {
+            return Err(error(&format!(
+                "it names both {} and {}",
+                face.opposite().letter(),
+                face.letter()
+            )));
+        }
+        faces.push(face);
+    }
+    if !faces.contains(&Face::U) {
+        return Err(error("it doesn't include U"));
+    }
+
+    let col = if faces.contains(&Face::L) {
+        0
+    } else if faces.contains(&Face::R) {
+        2
+    } else {
+        1
+    };
+    let row = if faces.contains(&Face::B) {
+        0
+    } else if faces.contains(&Face::F) {
+        2
+    } else {
+        1
+    };
+
+    Ok((Cubie { idx: row * 3 + col }, tail))
+}
+
+/// A permutation of the U layer: the cubie at index `idx` (numbered like `Cubie::idx`) moves to
+/// `targets[idx]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    pub targets: [u8; 9],
+}
+
+impl Default for Permutation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Permutation {
+    pub fn identity() -> Self {
+        Permutation {
+            targets: [0, 1, 2, 3, 4, 5, 6, 7, 8],
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// The permutation performed by turning the U layer clockwise `turns` times.
+    pub fn u_turn(turns: u32) -> Self {
+        let mut result = Self::identity();
+        for _ in 0..turns % 4 {
+            result = result.then(&Permutation { targets: U_TURN });
+        }
+        result
+    }
+
+    /// The permutation performed by doing `self` and then `other`.
+    pub fn then(&self, other: &Permutation) -> Permutation {
+        let mut targets = [0u8; 9];
+        for (idx, target) in targets.iter_mut().enumerate() {
+            *target = other.targets[self.targets[idx] as usize];
+        }
+        Permutation { targets }
+    }
+
+    /// The non-trivial cycles of the permutation, each starting from its lowest index.
+    pub fn cycles(&self) -> Vec<Vec<u8>> {
+        let mut seen = [false; 9];
+        let mut cycles = vec![];
+
+        for start in 0..9u8 {
+            if seen[start as usize] {
+                continue;
+            }
+
+            let mut cycle = vec![];
+            let mut idx = start;
+            while !seen[idx as usize] {
+                seen[idx as usize] = true;
+                cycle.push(idx);
+                idx = self.targets[idx as usize];
+            }
+
+            if cycle.len() > 1 {
+                cycles.push(cycle);
+            }
+        }
+
+        cycles
+    }
+
+    /// Whether the cubies selected by `filter` are moved by an odd permutation.
+    fn is_odd(&self, filter: impl Fn(&Cubie) -> bool) -> bool {
+        let swaps = self
+            .cycles()
+            .iter()
+            .filter(|cycle| filter(&Cubie { idx: cycle[0] }))
+            .map(|cycle| cycle.len() - 1)
+            .sum::<usize>();
+        swaps % 2 != 0
+    }
+
+    pub fn is_corner_permutation_odd(&self) -> bool {
+        self.is_odd(Cubie::is_corner)
+    }
+
+    pub fn is_edge_permutation_odd(&self) -> bool {
+        self.is_odd(Cubie::is_edge)
+    }
+
+    /// Express the permutation as arrows: swaps become a double-headed arrow, longer cycles
+    /// become a chain of single-headed arrows.
+    pub fn to_program(&self) -> Program {
+        let mut statements = vec![];
+
+        for cycle in self.cycles() {
+            let cubies = cycle
+                .iter()
+                .map(|idx| Cubie { idx: *idx })
+                .collect::<Vec<_>>();
+            statements.extend(cycle_statements(&cubies));
+        }
+
+        Program {
+            statements: Statements { statements },
+            side_stickers: None,
+        }
+    }
+}
+
+/// Follow the arrows in a program to find where each cubie goes. `a>b` moves the cubie at `a`
+/// to `b`, `a<b` moves the cubie at `b` to `a`, and `a<>b` swaps them.
+pub fn permutation_for_program(program: &Program) -> Result<Permutation> {
+    let mut targets: [Option<u8>; 9] = [None; 9];
+
+    for stmt in &program.statements.statements {
+        let moves = match stmt.op {
+            Operator::EndHead => vec![(stmt.start, stmt.end)],
+            Operator::StartHead => vec![(stmt.end, stmt.start)],
+            Operator::BothHead => vec![(stmt.start, stmt.end), (stmt.end, stmt.start)],
+        };
+        for (from, to) in moves {
+            match targets[from.idx as usize] {
+                Some(existing) if existing != to.idx => {
+                    return Err(anyhow!(
+                        "Cubie {} has arrows to both {} and {}",
+                        from.idx + 1,
+                        existing + 1,
+                        to.idx + 1
+                    ))
+                }
+                _ => targets[from.idx as usize] = Some(to.idx),
+            }
+        }
+    }
+
+    for (idx, target) in targets.iter().enumerate() {
+        if let Some(target) = target {
+            if targets[*target as usize].is_none() {
+                return Err(anyhow!(
+                    "Cubie {} has an arrow from {} but no arrow leaving it",
+                    target + 1,
+                    idx + 1
+                ));
+            }
+        }
+    }
+
+    let mut result = Permutation::identity();
+    for (idx, target) in targets.iter().enumerate() {
+        if let Some(target) = target {
+            result.targets[idx] = *target;
+        }
+    }
+
+    let mut sources: [Option<u8>; 9] = [None; 9];
+    for (idx, target) in result.targets.iter().enumerate() {
+        if let Some(source) = sources[*target as usize] {
+            return Err(anyhow!(
+                "Cubie {} has arrows from both {} and {}",
+                target + 1,
+                source + 1,
+                idx + 1
+            ));
+        }
+        sources[*target as usize] = Some(idx as u8);
+    }
+
+    Ok(result)
+}
+
+/// Check that `program` draws a PLL that can happen on a real cube, and return its
+/// permutation. Arrows have to join two corners or two edges, can't touch the center, can't
+/// contradict each other, and the corners and edges have to be swapped an even number of
+/// times between them.
+pub fn validate_program(program: &Program) -> Result<Permutation> {
+    for stmt in &program.statements.statements {
+        if stmt.start.is_center() || stmt.end.is_center() {
+            return Err(anyhow!(
+                "The arrow {} touches the center, which never moves",
+                stmt
+            ));
+        }
+        if stmt.start.is_corner() != stmt.end.is_corner() {
+            return Err(anyhow!("The arrow {} joins a corner and an edge", stmt));
+        }
+    }
+
+    let permutation = permutation_for_program(program)?;
+    let corners_odd = permutation.is_corner_permutation_odd();
+    let edges_odd = permutation.is_edge_permutation_odd();
+    if corners_odd != edges_odd {
+        let (odd, even) = if corners_odd {
+            ("corners", "edges")
+        } else {
+            ("edges", "corners")
+        };
+        return Err(anyhow!(
+            "The {} are swapped an odd number of times but the {} are not, so {} cannot happen",
+            odd,
+            even,
+            program
+        ));
+    }
+
+    Ok(permutation)
+}
+
+/// Build a cube showing the case drawn by `program`: solved apart from U layer cubies that
+/// have to follow the arrows to get home.
+pub fn cube_for_program(program: &Program) -> Result<Cube> {
+    let permutation = validate_program(program)?;
+
+    // The cubie that belongs at `target` is sitting at `idx`, so move it there from home.
+    let mut inverse = [0u8; 9];
+    for (idx, target) in permutation.targets.iter().enumerate() {
+        inverse[*target as usize] = idx as u8;
+    }
+
+    let mut cube = Cube::solved();
+    cube.permute_top_layer(&inverse)?;
+    Ok(cube)
+}
+
+/// The faces whose colors the side stickers show. `SideStickers::Auto` works them out from
+/// the arrows; without any side stickers, they are all unknown.
+pub fn side_sticker_faces(program: &Program) -> Result<[Option<Face>; 12]> {
+    match &program.side_stickers {
+        None => Ok([None; 12]),
+        Some(SideStickers::Explicit(faces)) => Ok(*faces),
+        Some(SideStickers::Auto) => Ok(side_sticker_faces_for_cube(&cube_for_program(program)?)),
+    }
+}
+
+/// The colors of the side stickers of `cube`'s U layer, in the order of `SIDE_STICKERS`.
+pub fn side_sticker_faces_for_cube(cube: &Cube) -> [Option<Face>; 12] {
+    let mut faces = [None; 12];
+    for (face, (idx, side)) in faces.iter_mut().zip(SIDE_STICKERS.iter()) {
+        // unwrap: every entry in SIDE_STICKERS is a sticker on that cubie.
+        let facelet = top_layer_facelets(*idx as usize)
+            .into_iter()
+            .find(|facelet| face_of_facelet(*facelet) == *side)
+            .unwrap();
+        *face = Some(cube.facelet(facelet));
+    }
+    faces
+}
+
+/// Compute the arrows for the case that `alg` solves by applying its inverse to a solved cube.
+pub fn program_for_algorithm(alg: &Algorithm) -> Result<Program> {
+    Ok(permutation_for_algorithm(alg)?.to_program())
+}
+
+pub fn permutation_for_algorithm(alg: &Algorithm) -> Result<Permutation> {
+    let mut cube = Cube::solved();
+    cube.apply_all(&alg.expand_inverse());
+    permutation_for_cube(&cube)
+}
+
+/// Find where each U layer cubie has to go to solve a cube whose first two layers are solved
+/// and whose U layer is oriented.
+pub fn permutation_for_cube(cube: &Cube) -> Result<Permutation> {
+    let top_color = cube.center(Face::U);
+    if cube
+        .face(Face::U)
+        .iter()
+        .any(|facelet| *facelet != top_color)
+    {
+        return Err(anyhow!(
+            "Cannot permute the U layer of a cube that is not oriented"
+        ));
+    }
+
+    top_layer_permutation(cube)
+}
+
+/// Find where each U layer cubie has to go to solve a cube whose first two layers are solved,
+/// whether or not the U layer is oriented.
+pub fn top_layer_permutation(cube: &Cube) -> Result<Permutation> {
+    if !cube.is_first_two_layers_solved() {
+        return Err(anyhow!(
+            "Cannot permute the U layer of a cube with unsolved F2L"
+        ));
+    }
+
+    let mut targets = [0u8; 9];
+    for (idx, target) in targets.iter_mut().enumerate() {
+        let mut colors = top_layer_facelets(idx)
+            .into_iter()
+            .map(|facelet| cube.facelet(facelet))
+            .collect::<Vec<_>>();
+        colors.sort_by_key(|face| face.index());
+
+        // unwrap: with F2L solved, every U layer cubie has a home in the U layer.
+        *target = (0..9)
+            .find(|home| {
+                let mut home_colors = top_layer_facelets(*home)
+                    .into_iter()
+                    .map(|facelet| cube.center(face_of_facelet(facelet)))
+                    .collect::<Vec<_>>();
+                home_colors.sort_by_key(|face| face.index());
+                home_colors == colors
+            })
+            .unwrap() as u8;
+    }
+
+    Ok(Permutation { targets })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> (usize, String) {
+        let err = parse_program(input).unwrap_err();
+        (err.offset(), err.to_string())
+    }
+
+    #[test]
+    fn caret_ignores_trailing_whitespace() {
+        assert_eq!(
+            error("1 ? 3    "),
+            (
+                2,
+                "Unexpected '?' while parsing Operator, expected one of '<', '>', '<>'\n  \
+                 1 ? 3    \n    ^"
+                    .to_string()
+            )
+        );
+        assert_eq!(error("1>3    x").0, 7);
+        assert_eq!(error("1>  ").0, 4);
+    }
+
+    #[test]
+    fn caret_handles_multibyte_input() {
+        assert_eq!(
+            error("1>é "),
+            (
+                2,
+                "Unexpected 'é' while parsing Cubie, expected one of '1'-'9', a position like \
+                 UFR\n  1>é \n    ^"
+                    .to_string()
+            )
+        );
+        assert_eq!(error("1>3 3>é ").0, 6);
+        assert_eq!(error("é").0, 0);
+    }
+
+    fn statements(input: &str) -> String {
+        parse_program(input).unwrap().to_string()
+    }
+
+    #[test]
+    fn cycles() {
+        assert_eq!(statements("(1 3 9)"), "1>3 3>9 9>1");
+        assert_eq!(statements("(1,3,9)"), "1>3 3>9 9>1");
+        assert_eq!(statements("(4 8 6)"), "4>8 8>6 6>4");
+        assert_eq!(statements("(1 3 9)(2 8)"), "1>3 3>9 9>1 2<>8");
+        assert_eq!(statements(" ( 2 8 ) 1>3 "), "2<>8 1>3");
+
+        let err = parse_program("(1)").unwrap_err();
+        assert_eq!(err.message(), "A cycle needs at least two cubies");
+        assert_eq!(err.offset(), 1);
+        assert_eq!(parse_program("(1 3").unwrap_err().offset(), 4);
+    }
+
+    #[test]
+    fn chains() {
+        assert_eq!(statements("1>3>9>1"), "1>3 3>9 9>1");
+        assert_eq!(statements("1 < 3 <> 9"), "1<3 3<>9");
+    }
+
+    #[test]
+    fn named_positions() {
+        assert_eq!(statements("UBL>UBR"), "1>3");
+        assert_eq!(statements("URB>FRU"), "3>9");
+        assert_eq!(statements("ub<>uf"), "2<>8");
+        assert_eq!(statements("(UL UR UF)"), "4>6 6>8 8>4");
+        assert_eq!(statements("U>2"), "5>2");
+
+        for (input, message) in [
+            ("UX>1", "'UX' is not a U layer position: 'X' is not a face"),
+            ("UUF>1", "'UUF' is not a U layer position: it names U twice"),
+            (
+                "ULR>1",
+                "'ULR' is not a U layer position: it names both L and R",
+            ),
+            (
+                "FR>1",
+                "'FR' is not a U layer position: it doesn't include U",
+            ),
+        ] {
+            let err = parse_program(input).unwrap_err();
+            assert_eq!(err.message(), message, "{}", input);
+            assert_eq!(err.offset(), 0, "{}", input);
+        }
+        assert_eq!(parse_program("1>UFX").unwrap_err().offset(), 2);
+    }
+
+    #[test]
+    fn side_stickers() {
+        assert_eq!(
+            parse_program("1>3 {auto}").unwrap().side_stickers,
+            Some(SideStickers::Auto)
+        );
+        assert_eq!(
+            statements("{ F R B L . . . . . . . . }"),
+            "{FRB L.. ... ...}"
+        );
+        assert_eq!(parse_program("{F R}").unwrap_err().offset(), 4);
+        assert_eq!(parse_program("{auto} 1").unwrap_err().offset(), 7);
+    }
+
+    fn validate(input: &str) -> Result<Permutation> {
+        validate_program(&parse_program(input).unwrap())
+    }
+
+    #[test]
+    fn impossible_programs() {
+        for (input, message) in [
+            ("1>5", "The arrow 1>5 touches the center, which never moves"),
+            ("1>2", "The arrow 1>2 joins a corner and an edge"),
+            ("1<>3 3<>9", "Cubie 3 has arrows to both 1 and 9"),
+            ("1>3", "Cubie 3 has an arrow from 1 but no arrow leaving it"),
+            ("1>3 3>1 9>1", "Cubie 1 has arrows from both 3 and 9"),
+            (
+                "1<>3",
+                "The corners are swapped an odd number of times but the edges are not, so 1<>3 \
+                 cannot happen",
+            ),
+            (
+                "2<>8",
+                "The edges are swapped an odd number of times but the corners are not, so 2<>8 \
+                 cannot happen",
+            ),
+        ] {
+            assert_eq!(validate(input).unwrap_err().to_string(), message);
+        }
+    }
+
+    #[test]
+    fn legal_programs() {
+        // T: swap the right corners and the left and right edges.
+        assert_eq!(
+            validate("3<>9 4<>6").unwrap().targets,
+            [0, 1, 8, 5, 4, 3, 6, 7, 2]
+        );
+        // U: cycle three edges.
+        assert_eq!(
+            validate("(4 8 6)").unwrap().targets,
+            [0, 1, 2, 7, 4, 3, 6, 5, 8]
+        );
+        assert_eq!(validate("").unwrap(), Permutation::identity());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::raster::Canvas;
+use crate::scene::{Renderer, Scene};
+use crate::Result;
+use anyhow::anyhow;
+
+/// Scene coordinates are SVG pixels, which are defined to be 1/96 of an inch.
+pub const SVG_DPI: f64 = 96.0;
+
+/// Renders a scene as a PNG image, drawing the shapes itself. Text isn't drawn, since there's
+/// no font to draw it with.
+#[derive(Debug)]
+pub struct PngRenderer {
+    dpi: f64,
+}
+
+impl Default for PngRenderer {
+    fn default() -> Self {
+        PngRenderer { dpi: SVG_DPI }
+    }
+}
+
+impl PngRenderer {
+    /// A renderer that makes one PNG pixel for each SVG pixel.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A renderer that draws the scene at `dpi` dots per inch, so 192 makes an image twice as
+    /// wide as `new` would. The resolution is recorded in the PNG as well.
+    pub fn with_dpi(dpi: f64) -> Self {
+        PngRenderer { dpi }
+    }
+}
+
+impl Renderer for PngRenderer {
+    type Output = Result<Vec<u8>>;
+
+    fn render(&mut self, scene: &Scene) -> Result<Vec<u8>> {
+        if !(self.dpi > 0.0 && self.dpi.is_finite()) {
+            return Err(anyhow!(
+                "The resolution must be more than 0 dpi, not {}",
+                self.dpi
+            ));
+        }
+
+        let canvas = Canvas::render(scene, self.dpi / SVG_DPI)?;
+        encode(&canvas, self.dpi)
+    }
+}
+
+fn encode(canvas: &Canvas, dpi: f64) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            data.extend_from_slice(&canvas.pixel(x, y));
+        }
+    }
+
+    let mut output = vec![];
+    let mut encoder = png::Encoder::new(&mut output, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let pixels_per_meter = (dpi / 0.0254).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_meter,
+        yppu: pixels_per_meter,
+        unit: png::Unit::Meter,
+    }));
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Shape;
+
+    /// Render `scene` at `dpi` and decode it again, giving the size and the RGBA pixels.
+    fn render(scene: &Scene, dpi: f64) -> (u32, u32, Vec<u8>) {
+        let png = PngRenderer::with_dpi(dpi).render(scene).unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        (info.width, info.height, pixels)
+    }
+
+    fn pixel((width, _, pixels): &(u32, u32, Vec<u8>), x: u32, y: u32) -> [u8; 4] {
+        let idx = ((y * width + x) * 4) as usize;
+        [
+            pixels[idx],
+            pixels[idx + 1],
+            pixels[idx + 2],
+            pixels[idx + 3],
+        ]
+    }
+
+    fn square_scene() -> Scene {
+        let mut scene = Scene::new(10.0, 10.0);
+        scene.push(Shape::Rect {
+            x: 2.0,
+            y: 2.0,
+            width: 4.0,
+            height: 4.5,
+            fill: "#ff0000".into(),
+            stroke: None,
+        });
+        scene
+    }
+
+    #[test]
+    fn draws_shapes() {
+        let image = render(&square_scene(), SVG_DPI);
+        assert_eq!((image.0, image.1), (10, 10));
+        assert_eq!(pixel(&image, 4, 4), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 8, 8)[3], 0);
+        assert_eq!(pixel(&image, 1, 4)[3], 0);
+        // The bottom edge covers half of its row of pixels.
+        assert_eq!(pixel(&image, 4, 6), [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn scales_with_resolution() {
+        let image = render(&square_scene(), 2.0 * SVG_DPI);
+        assert_eq!((image.0, image.1), (20, 20));
+        assert_eq!(pixel(&image, 4, 4), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 3, 3)[3], 0);
+        assert_eq!(pixel(&image, 11, 12), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 12, 12)[3], 0);
+        assert_eq!(pixel(&image, 11, 13)[3], 0);
+    }
+
+    #[test]
+    fn resolution_is_bounded() {
+        let scene = Scene::new(100.0, 100.0);
+        for dpi in [0.0, -96.0, f64::NAN, f64::INFINITY, 1e30, 1e6] {
+            assert!(
+                PngRenderer::with_dpi(dpi).render(&scene).is_err(),
+                "{}",
+                dpi
+            );
+        }
+        assert!(PngRenderer::with_dpi(192.0).render(&scene).is_ok());
+    }
+}
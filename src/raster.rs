@@ -0,0 +1,370 @@
+use crate::colors::{rgb, Pattern};
+use crate::scene::{Marker, Paint, Scene, Shape, Stroke};
+use crate::Result;
+use anyhow::anyhow;
+
+// Each pixel is sampled on a grid this many samples across to smooth the edges of shapes.
+const SAMPLES: usize = 4;
+
+// The largest canvas that will be drawn, so a huge scale fails cleanly instead of running out
+// of memory. At 16 bytes a pixel, the most pixels in all comes to 256MiB.
+const MAX_SIDE: f64 = 16384.0;
+const MAX_PIXELS: f64 = 16.0 * 1024.0 * 1024.0;
+
+type Point = (f64, f64);
+
+/// The paint for a shape with its colors worked out, in pixel units.
+enum Fill {
+    Solid([f32; 3]),
+    Pattern {
+        color: [f32; 3],
+        pattern: Pattern,
+        size: f64,
+        scale: f64,
+    },
+}
+
+impl Fill {
+    fn new(paint: &Paint, scale: f64) -> Result<Fill> {
+        let fill = match paint {
+            Paint::Color(color) => Fill::Solid(to_f32(rgb(color)?)),
+            Paint::Pattern {
+                color,
+                pattern,
+                size,
+            } => Fill::Pattern {
+                color: to_f32(rgb(color)?),
+                pattern: *pattern,
+                size: *size,
+                scale,
+            },
+        };
+        Ok(fill)
+    }
+
+    fn color_at(&self, (x, y): Point) -> [f32; 3] {
+        match *self {
+            Fill::Solid(color) => color,
+            Fill::Pattern {
+                color,
+                pattern,
+                size,
+                scale,
+            } => {
+                // The patterns match the SVG ones: lines one unit wide in 60% black, on tiles
+                // that start at the origin.
+                let x = (x / scale).rem_euclid(size);
+                let y = (y / scale).rem_euclid(size);
+                if pattern_ink(pattern, size, x, y) {
+                    color.map(|c| c * 0.4)
+                } else {
+                    color
+                }
+            }
+        }
+    }
+}
+
+/// Whether (`x`, `y`) on a pattern tile `size` across is covered by the pattern's lines.
+fn pattern_ink(pattern: Pattern, size: f64, x: f64, y: f64) -> bool {
+    let half = (size / 2.0).floor();
+    // The distance from a diagonal line through the tile's corners, which repeats every tile.
+    let diagonal = |d: f64| {
+        let d = d.rem_euclid(size);
+        d.min(size - d) / 2f64.sqrt() <= 0.5
+    };
+    match pattern {
+        Pattern::Solid => false,
+        Pattern::Hatch => diagonal(x + y),
+        Pattern::BackHatch => diagonal(x - y),
+        Pattern::CrossHatch => diagonal(x + y) || diagonal(x - y),
+        Pattern::Dots => (x - half).abs() <= 1.5 && (y - half).abs() <= 1.5,
+        Pattern::HorizontalStripes => (y - half).abs() <= 0.5,
+        Pattern::VerticalStripes => (x - half).abs() <= 0.5,
+    }
+}
+
+pub(crate) struct Canvas {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    // Premultiplied red, green, blue and alpha, from 0 to 1.
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+        }
+    }
+
+    /// Draw `scene` with each of its units `scale` pixels across.
+    pub(crate) fn render(scene: &Scene, scale: f64) -> Result<Canvas> {
+        let width = (scene.width * scale).ceil();
+        let height = (scene.height * scale).ceil();
+        let fits = |side: f64| (0.0..=MAX_SIDE).contains(&side);
+        if !(fits(width) && fits(height) && width * height <= MAX_PIXELS) {
+            return Err(anyhow!(
+                "The image would be {}x{} pixels, but it can be at most {} pixels on a side \
+                 and {} pixels in all",
+                width,
+                height,
+                MAX_SIDE,
+                MAX_PIXELS
+            ));
+        }
+
+        let mut canvas = Canvas::new(width as u32, height as u32);
+        for shape in &scene.shapes {
+            canvas.draw(shape, scale)?;
+        }
+        Ok(canvas)
+    }
+
+    /// The red, green, blue and alpha of the pixel at (`x`, `y`).
+    pub(crate) fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let [r, g, b, a] = self.pixels[self.index(x, y)];
+        let unmultiply = |c: f32| if a > 0.0 { c / a } else { 0.0 };
+        [unmultiply(r), unmultiply(g), unmultiply(b), a]
+            .map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    fn draw(&mut self, shape: &Shape, scale: f64) -> Result<()> {
+        let scaled = |(x, y): Point| (x * scale, y * scale);
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => {
+                let points = [
+                    (*x, *y),
+                    (x + width, *y),
+                    (x + width, y + height),
+                    (*x, y + height),
+                ]
+                .map(scaled);
+                self.fill_polygon(&points, &Fill::new(fill, scale)?);
+                if let Some(stroke) = stroke {
+                    self.outline(&points, stroke, scale)?;
+                }
+            }
+            Shape::Polygon {
+                points,
+                fill,
+                stroke,
+            } => {
+                let points = points.iter().copied().map(scaled).collect::<Vec<_>>();
+                self.fill_polygon(&points, &Fill::new(fill, scale)?);
+                if let Some(stroke) = stroke {
+                    self.outline(&points, stroke, scale)?;
+                }
+            }
+            Shape::Line {
+                from,
+                to,
+                stroke,
+                start,
+                end,
+            } => {
+                let (from, to) = (scaled(*from), scaled(*to));
+                let width = stroke.width * scale;
+                let fill = Fill::Solid(to_f32(rgb(&stroke.color)?));
+                self.fill_polygon(&segment(from, to, width, 0.0), &fill);
+                if *start == Marker::Arrow {
+                    self.fill_polygon(&arrowhead(from, to, width), &fill);
+                }
+                if *end == Marker::Arrow {
+                    self.fill_polygon(&arrowhead(to, from, width), &fill);
+                }
+            }
+            Shape::Text { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Stroke the edges of a polygon. The ends of each edge are extended by half the stroke
+    /// width, which makes square corners on rectangles like SVG's mitered ones.
+    fn outline(&mut self, points: &[Point], stroke: &Stroke, scale: f64) -> Result<()> {
+        let width = stroke.width * scale;
+        let fill = Fill::Solid(to_f32(rgb(&stroke.color)?));
+        for (idx, from) in points.iter().enumerate() {
+            let to = points[(idx + 1) % points.len()];
+            self.fill_polygon(&segment(*from, to, width, width / 2.0), &fill);
+        }
+        Ok(())
+    }
+
+    fn fill_polygon(&mut self, points: &[Point], fill: &Fill) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let min_x = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+        let clamp = |value: f64, limit: u32| value.max(0.0).min(limit as f64) as u32;
+        let (x0, x1) = (
+            clamp(min_x.floor(), self.width),
+            clamp(max_x.ceil(), self.width),
+        );
+        let (y0, y1) = (
+            clamp(min_y.floor(), self.height),
+            clamp(max_y.ceil(), self.height),
+        );
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let mut color = [0.0; 3];
+                let mut hits = 0;
+                for sy in 0..SAMPLES {
+                    for sx in 0..SAMPLES {
+                        let sample = (
+                            px as f64 + (sx as f64 + 0.5) / SAMPLES as f64,
+                            py as f64 + (sy as f64 + 0.5) / SAMPLES as f64,
+                        );
+                        if contains(points, sample) {
+                            let sample_color = fill.color_at(sample);
+                            for (c, s) in color.iter_mut().zip(sample_color.iter()) {
+                                *c += s;
+                            }
+                            hits += 1;
+                        }
+                    }
+                }
+                if hits > 0 {
+                    let color = color.map(|c| c / hits as f32);
+                    let coverage = hits as f32 / (SAMPLES * SAMPLES) as f32;
+                    self.blend(px, py, color, coverage);
+                }
+            }
+        }
+    }
+
+    /// Paint an opaque `color` over the pixel, covering `coverage` of it.
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 3], coverage: f32) {
+        let index = self.index(x, y);
+        let pixel = &mut self.pixels[index];
+        for (channel, c) in pixel.iter_mut().zip(color.iter()) {
+            *channel = c * coverage + *channel * (1.0 - coverage);
+        }
+        pixel[3] = coverage + pixel[3] * (1.0 - coverage);
+    }
+}
+
+/// Whether `point` is inside the polygon, by the even-odd rule.
+fn contains(points: &[Point], (x, y): Point) -> bool {
+    let mut inside = false;
+    for (idx, a) in points.iter().enumerate() {
+        let b = points[(idx + 1) % points.len()];
+        if (a.1 > y) != (b.1 > y) {
+            let crossing = a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0);
+            if x < crossing {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// The rectangle covered by a line `width` wide from `from` to `to`, with its ends pushed out
+/// by `extend`.
+fn segment(from: Point, to: Point, width: f64, extend: f64) -> [Point; 4] {
+    let (dx, dy) = unit(from, to);
+    let (from, to) = (
+        (from.0 - dx * extend, from.1 - dy * extend),
+        (to.0 + dx * extend, to.1 + dy * extend),
+    );
+    let (nx, ny) = (-dy * width / 2.0, dx * width / 2.0);
+    [
+        (from.0 + nx, from.1 + ny),
+        (to.0 + nx, to.1 + ny),
+        (to.0 - nx, to.1 - ny),
+        (from.0 - nx, from.1 - ny),
+    ]
+}
+
+/// The arrowhead at `tip` of a line coming from `tail`. This is the SVG arrow marker: the
+/// triangle (0,0) (10,5) (0,10) centered on the end of the line, three stroke widths across.
+fn arrowhead(tip: Point, tail: Point, width: f64) -> [Point; 3] {
+    let (dx, dy) = unit(tail, tip);
+    let size = width * 3.0 / 10.0;
+    let corner = |u: f64, v: f64| {
+        let (u, v) = ((u - 5.0) * size, (v - 5.0) * size);
+        (tip.0 + dx * u - dy * v, tip.1 + dy * u + dx * v)
+    };
+    [corner(0.0, 0.0), corner(10.0, 5.0), corner(0.0, 10.0)]
+}
+
+fn unit(from: Point, to: Point) -> Point {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        (1.0, 0.0)
+    } else {
+        (dx / length, dy / length)
+    }
+}
+
+fn to_f32(color: [u8; 3]) -> [f32; 3] {
+    color.map(|c| c as f32 / 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The pattern's covered points on a tile `size` across, sampled at pixel centers.
+    fn ink(pattern: Pattern, size: usize) -> Vec<Vec<bool>> {
+        (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| pattern_ink(pattern, size as f64, x as f64 + 0.5, y as f64 + 0.5))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pattern_tiles() {
+        assert!(ink(Pattern::Solid, 6)
+            .iter()
+            .flatten()
+            .all(|covered| !covered));
+
+        let stripes = ink(Pattern::HorizontalStripes, 6);
+        assert!(stripes[3].iter().all(|covered| *covered));
+        assert!(!stripes[1].iter().any(|covered| *covered));
+
+        let stripes = ink(Pattern::VerticalStripes, 6);
+        assert!(stripes.iter().all(|row| row[3] && !row[1]));
+
+        let dots = ink(Pattern::Dots, 8);
+        assert!(dots[4][4] && !dots[0][0] && !dots[4][0]);
+
+        let hatch = ink(Pattern::Hatch, 6);
+        let back_hatch = ink(Pattern::BackHatch, 6);
+        let cross_hatch = ink(Pattern::CrossHatch, 6);
+        for y in 0..6 {
+            for x in 0..6 {
+                // Hatch rises to the right, so it's its own mirror image across the other
+                // diagonal, and back hatch is hatch flipped left to right.
+                assert_eq!(hatch[y][x], hatch[5 - x][5 - y]);
+                assert_eq!(back_hatch[y][x], hatch[y][5 - x]);
+                assert_eq!(cross_hatch[y][x], hatch[y][x] || back_hatch[y][x]);
+            }
+        }
+        assert!(hatch[0][5] && !hatch[0][0] && back_hatch[0][0]);
+    }
+}
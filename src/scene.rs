@@ -0,0 +1,122 @@
+use crate::colors::Pattern;
+
+/// A diagram as a list of shapes, drawn in order, so that each diagram is laid out once and
+/// then handed to whichever `Renderer` produces the output format. Coordinates are in pixels
+/// with the origin at the top left.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub width: f64,
+    pub height: f64,
+    pub shapes: Vec<Shape>,
+}
+
+impl Scene {
+    pub fn new(width: f64, height: f64) -> Self {
+        Scene {
+            width,
+            height,
+            shapes: vec![],
+        }
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+}
+
+impl Extend<Shape> for Scene {
+    fn extend<I: IntoIterator<Item = Shape>>(&mut self, shapes: I) {
+        self.shapes.extend(shapes);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: Paint,
+        stroke: Option<Stroke>,
+    },
+    Polygon {
+        points: Vec<(f64, f64)>,
+        fill: Paint,
+        stroke: Option<Stroke>,
+    },
+    Line {
+        from: (f64, f64),
+        to: (f64, f64),
+        stroke: Stroke,
+        start: Marker,
+        end: Marker,
+    },
+    /// Text centered on (`x`, `y`).
+    Text {
+        x: f64,
+        y: f64,
+        text: String,
+        size: f64,
+        fill: String,
+    },
+}
+
+/// How the inside of a shape is filled.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    Color(String),
+    /// A color with a pattern drawn over it in translucent black. `size` is the width of the
+    /// tile that the pattern repeats.
+    Pattern {
+        color: String,
+        pattern: Pattern,
+        size: f64,
+    },
+}
+
+impl Paint {
+    /// The color underneath any pattern.
+    pub fn color(&self) -> &str {
+        match self {
+            Paint::Color(color) => color,
+            Paint::Pattern { color, .. } => color,
+        }
+    }
+}
+
+impl From<&str> for Paint {
+    fn from(color: &str) -> Self {
+        Paint::Color(color.to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub color: String,
+    pub width: f64,
+}
+
+impl Stroke {
+    pub fn new(color: &str, width: f64) -> Self {
+        Stroke {
+            color: color.to_string(),
+            width,
+        }
+    }
+}
+
+/// What is drawn at the end of a line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Marker {
+    None,
+    /// An arrowhead in the line's color, pointing away from the line.
+    Arrow,
+}
+
+/// Something that turns a scene into an output format.
+pub trait Renderer {
+    type Output;
+
+    fn render(&mut self, scene: &Scene) -> Self::Output;
+}
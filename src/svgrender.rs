@@ -0,0 +1,284 @@
+use crate::colors::Pattern;
+use crate::scene::{Marker, Paint, Renderer, Scene, Shape, Stroke};
+use crate::svg::{Element, Path};
+
+/// Renders a scene as SVG text.
+#[derive(Debug, Default)]
+pub struct SvgRenderer {
+    // The colors of the arrowheads and the patterns defined for the scene being rendered.
+    // Their position in the list gives their id.
+    arrows: Vec<String>,
+    patterns: Vec<Paint>,
+    compact: bool,
+}
+
+impl SvgRenderer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A renderer that writes the whole SVG on one line.
+    pub fn compact() -> Self {
+        SvgRenderer {
+            compact: true,
+            ..Default::default()
+        }
+    }
+
+    /// The SVG element tree for `scene`.
+    pub fn element(&mut self, scene: &Scene) -> Element {
+        self.collect_defs(scene);
+
+        let mut svg = Element::new("svg")
+            .attr("xmlns", "http://www.w3.org/2000/svg")
+            .attr("height", &scene.height.to_string())
+            .attr("width", &scene.width.to_string());
+
+        if !self.arrows.is_empty() {
+            svg.push(self.arrow_defs());
+        }
+        if !self.patterns.is_empty() {
+            svg.push(self.pattern_defs());
+        }
+        for shape in &scene.shapes {
+            svg.push(self.shape(shape));
+        }
+
+        svg
+    }
+
+    fn arrow_id(&self, color: &str) -> String {
+        // unwrap: every arrow color is collected before any shape is drawn.
+        let idx = self.arrows.iter().position(|c| c == color).unwrap();
+        if idx == 0 {
+            "arrow".to_string()
+        } else {
+            format!("arrow-{}", idx + 1)
+        }
+    }
+
+    fn arrow_url(&self, color: &str) -> String {
+        format!("url(#{})", self.arrow_id(color))
+    }
+
+    fn fill(&self, paint: &Paint) -> String {
+        match paint {
+            Paint::Color(color) => color.clone(),
+            Paint::Pattern { .. } => {
+                // unwrap: every pattern is collected before any shape is drawn.
+                let idx = self.patterns.iter().position(|p| p == paint).unwrap();
+                format!("url(#pattern-{})", idx + 1)
+            }
+        }
+    }
+
+    /// Find the arrowheads and patterns that need to be defined before they're used.
+    fn collect_defs(&mut self, scene: &Scene) {
+        self.arrows.clear();
+        self.patterns.clear();
+
+        for shape in &scene.shapes {
+            match shape {
+                Shape::Rect { fill, .. } | Shape::Polygon { fill, .. } => {
+                    if let Paint::Pattern { .. } = fill {
+                        if !self.patterns.contains(fill) {
+                            self.patterns.push(fill.clone());
+                        }
+                    }
+                }
+                Shape::Line {
+                    stroke, start, end, ..
+                } => {
+                    let has_arrow = *start == Marker::Arrow || *end == Marker::Arrow;
+                    if has_arrow && !self.arrows.contains(&stroke.color) {
+                        self.arrows.push(stroke.color.clone());
+                    }
+                }
+                Shape::Text { .. } => {}
+            }
+        }
+    }
+
+    fn arrow_defs(&self) -> Element {
+        Element::new("defs").children(self.arrows.iter().map(|color| {
+            let path = Path::new().M(0.0, 0.0).L(10.0, 5.0).L(0.0, 10.0).z();
+            Element::new("marker")
+                .attr("id", &self.arrow_id(color))
+                .attr("viewBox", "0 0 10 10")
+                .attr("refX", "5")
+                .attr("refY", "5")
+                .attr("markerWidth", "3")
+                .attr("markerHeight", "3")
+                .attr("orient", "auto-start-reverse")
+                .child(
+                    Element::new("path")
+                        .attr("d", &path.to_string())
+                        .attr("fill", color),
+                )
+        }))
+    }
+
+    fn pattern_defs(&self) -> Element {
+        let mut defs = Element::new("defs");
+        for (idx, paint) in self.patterns.iter().enumerate() {
+            let (color, pattern, size) = match paint {
+                Paint::Pattern {
+                    color,
+                    pattern,
+                    size,
+                } => (color, *pattern, *size),
+                Paint::Color(_) => continue,
+            };
+
+            // Tiles an odd number of pixels across keep their dots and stripes on whole pixels.
+            let half = (size / 2.0).floor();
+            let overlay = match pattern {
+                Pattern::Solid => Path::new(),
+                Pattern::Hatch => Path::new()
+                    .M(0.0, size)
+                    .L(size, 0.0)
+                    .M(-1.0, 1.0)
+                    .L(1.0, -1.0)
+                    .M(size - 1.0, size + 1.0)
+                    .L(size + 1.0, size - 1.0),
+                Pattern::BackHatch => Path::new()
+                    .M(0.0, 0.0)
+                    .L(size, size)
+                    .M(-1.0, size - 1.0)
+                    .L(1.0, size + 1.0)
+                    .M(size - 1.0, -1.0)
+                    .L(size + 1.0, 1.0),
+                Pattern::CrossHatch => Path::new()
+                    .M(0.0, 0.0)
+                    .L(size, size)
+                    .M(0.0, size)
+                    .L(size, 0.0),
+                Pattern::Dots => Path::new()
+                    .M(half - 1.0, half - 1.0)
+                    .h(2.0)
+                    .v(2.0)
+                    .h(-2.0)
+                    .z(),
+                Pattern::HorizontalStripes => Path::new().M(0.0, half).h(size),
+                Pattern::VerticalStripes => Path::new().M(half, 0.0).v(size),
+            };
+
+            let tile = Element::new("pattern")
+                .attr("id", &format!("pattern-{}", idx + 1))
+                .attr("patternUnits", "userSpaceOnUse")
+                .attr("width", &size.to_string())
+                .attr("height", &size.to_string())
+                .child(rect(0.0, 0.0, size, size, color, None))
+                .child(
+                    Element::new("path")
+                        .attr("d", &overlay.to_string())
+                        .attr("stroke", "black")
+                        .attr("stroke-width", "1")
+                        .attr("fill", "black")
+                        .attr("opacity", "0.6"),
+                );
+            defs.push(tile);
+        }
+
+        defs
+    }
+
+    fn shape(&self, shape: &Shape) -> Element {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                stroke,
+            } => rect(*x, *y, *width, *height, &self.fill(fill), stroke.as_ref()),
+            Shape::Polygon {
+                points,
+                fill,
+                stroke,
+            } => {
+                let points = points
+                    .iter()
+                    .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let element = Element::new("polygon")
+                    .attr("fill", &self.fill(fill))
+                    .attr("points", &points);
+                with_stroke(element, stroke.as_ref())
+            }
+            Shape::Line {
+                from,
+                to,
+                stroke,
+                start,
+                end,
+            } => {
+                let mut element = Element::new("line")
+                    .attr("x1", &from.0.to_string())
+                    .attr("y1", &from.1.to_string())
+                    .attr("x2", &to.0.to_string())
+                    .attr("y2", &to.1.to_string())
+                    .attr("stroke-width", &stroke.width.to_string())
+                    .attr("stroke", &stroke.color);
+                if *start == Marker::Arrow {
+                    element = element.attr("marker-start", &self.arrow_url(&stroke.color));
+                }
+                if *end == Marker::Arrow {
+                    element = element.attr("marker-end", &self.arrow_url(&stroke.color));
+                }
+                element
+            }
+            Shape::Text {
+                x,
+                y,
+                text,
+                size,
+                fill,
+            } => Element::new("text")
+                .attr("x", &x.to_string())
+                .attr("y", &y.to_string())
+                .attr("font-size", &size.to_string())
+                .attr("font-family", "sans-serif")
+                .attr("text-anchor", "middle")
+                .attr("dominant-baseline", "central")
+                .attr("fill", fill)
+                .child(text.as_str()),
+        }
+    }
+}
+
+impl Renderer for SvgRenderer {
+    type Output = String;
+
+    fn render(&mut self, scene: &Scene) -> String {
+        let svg = self.element(scene);
+        if self.compact {
+            svg.compact()
+        } else {
+            svg.pretty()
+        }
+    }
+}
+
+fn rect(x: f64, y: f64, width: f64, height: f64, fill: &str, stroke: Option<&Stroke>) -> Element {
+    let path = Path::new().M(x, y).h(width).v(height).h(-width).v(-height);
+
+    let element = Element::new("path").attr("fill", fill);
+    let element = match stroke {
+        None => element.attr("border-width", "0"),
+        Some(stroke) => with_stroke(element, Some(stroke)),
+    };
+    element.attr("d", &path.to_string())
+}
+
+fn with_stroke(element: Element, stroke: Option<&Stroke>) -> Element {
+    match stroke {
+        None => element,
+        Some(stroke) => element
+            .attr("stroke", &stroke.color)
+            .attr("stroke-width", &stroke.width.to_string()),
+    }
+}
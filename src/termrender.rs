@@ -0,0 +1,128 @@
+use crate::raster::Canvas;
+use crate::scene::{Renderer, Scene};
+use crate::Result;
+use anyhow::anyhow;
+
+/// Renders a scene as text for a terminal that understands 24-bit ANSI colors. Each character
+/// is a half block, showing one pixel in its foreground color over another in its background
+/// color, so pixels come out roughly square.
+#[derive(Debug)]
+pub struct TerminalRenderer {
+    columns: u32,
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        TerminalRenderer { columns: 32 }
+    }
+}
+
+impl TerminalRenderer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A renderer that scales the scene to be `columns` characters wide.
+    pub fn with_columns(columns: u32) -> Self {
+        TerminalRenderer { columns }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    type Output = Result<String>;
+
+    fn render(&mut self, scene: &Scene) -> Result<String> {
+        if self.columns == 0 || scene.width <= 0.0 {
+            return Err(anyhow!("There's no room to draw a preview"));
+        }
+
+        let canvas = Canvas::render(scene, self.columns as f64 / scene.width)?;
+
+        let mut output = String::new();
+        for y in (0..canvas.height).step_by(2) {
+            for x in 0..canvas.width {
+                let top = canvas.pixel(x, y);
+                let bottom = if y + 1 < canvas.height {
+                    canvas.pixel(x, y + 1)
+                } else {
+                    [0; 4]
+                };
+                output.push_str(&cell(top, bottom));
+            }
+            output.push_str("\x1b[0m\n");
+        }
+
+        Ok(output)
+    }
+}
+
+/// A character showing `top` above `bottom`. Mostly transparent pixels are left for the
+/// terminal's own background to show through.
+fn cell(top: [u8; 4], bottom: [u8; 4]) -> String {
+    let opaque = |pixel: [u8; 4]| pixel[3] >= 128;
+    match (opaque(top), opaque(bottom)) {
+        (true, true) => format!("{}{}\u{2580}", foreground(top), background(bottom)),
+        (true, false) => format!("\x1b[49m{}\u{2580}", foreground(top)),
+        (false, true) => format!("\x1b[49m{}\u{2584}", foreground(bottom)),
+        (false, false) => "\x1b[0m ".to_string(),
+    }
+}
+
+fn foreground([r, g, b, _]: [u8; 4]) -> String {
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
+}
+
+fn background([r, g, b, _]: [u8; 4]) -> String {
+    format!("\x1b[48;2;{};{};{}m", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Shape;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 200];
+    const CLEAR: [u8; 4] = [0, 255, 0, 100];
+
+    #[test]
+    fn cells() {
+        assert_eq!(
+            cell(RED, BLUE),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}"
+        );
+        assert_eq!(cell(RED, CLEAR), "\x1b[49m\x1b[38;2;255;0;0m\u{2580}");
+        assert_eq!(cell(CLEAR, BLUE), "\x1b[49m\x1b[38;2;0;0;255m\u{2584}");
+        assert_eq!(cell(CLEAR, CLEAR), "\x1b[0m ");
+    }
+
+    #[test]
+    fn renders_two_pixels_per_character() {
+        let mut scene = Scene::new(4.0, 3.0);
+        scene.push(Shape::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 2.0,
+            height: 3.0,
+            fill: "#ff0000".into(),
+            stroke: None,
+        });
+        let output = TerminalRenderer::with_columns(4).render(&scene).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let full = cell(RED, RED);
+        let top_only = cell(RED, [0; 4]);
+        let empty = cell([0; 4], [0; 4]);
+        assert_eq!(lines[0], format!("{0}{0}{1}{1}\x1b[0m", full, empty));
+        assert_eq!(lines[1], format!("{0}{0}{1}{1}\x1b[0m", top_only, empty));
+    }
+
+    #[test]
+    fn needs_room() {
+        let scene = Scene::new(4.0, 3.0);
+        assert!(TerminalRenderer::with_columns(0).render(&scene).is_err());
+        assert!(TerminalRenderer::new()
+            .render(&Scene::new(0.0, 3.0))
+            .is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::algspec::Algorithm;
+use crate::cube::{Cube, Face, Move, AUF_ORDER};
+use crate::ollspec::{cube_for_desc, Direction};
+use crate::pllspec::{cube_for_program, Program};
+use crate::Result;
+use anyhow::anyhow;
+use std::fmt;
+
+/// The U turns that make an algorithm solve a case.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Verification {
+    pub pre_auf: Option<Move>,
+    pub post_auf: Option<Move>,
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.pre_auf, self.post_auf) {
+            (None, None) => write!(f, "no AUF"),
+            (Some(pre), None) => write!(f, "pre-AUF {}", pre),
+            (None, Some(post)) => write!(f, "post-AUF {}", post),
+            (Some(pre), Some(post)) => write!(f, "pre-AUF {}, post-AUF {}", pre, post),
+        }
+    }
+}
+
+/// Check that `alg` orients the U layer of the case drawn by `desc`, allowing an AUF first.
+pub fn verify_oll(desc: &[Direction], alg: &Algorithm) -> Result<Verification> {
+    let case = cube_for_desc(desc)?;
+    verify(&case, alg, |cube| {
+        let top_color = cube.center(Face::U);
+        cube.is_first_two_layers_solved()
+            && cube
+                .face(Face::U)
+                .iter()
+                .all(|facelet| *facelet == top_color)
+    })
+    .ok_or_else(|| anyhow!("'{}' does not orient the pictured case", alg))
+}
+
+/// Check that `alg` solves the case drawn by `program`, allowing an AUF before and after.
+pub fn verify_pll(program: &Program, alg: &Algorithm) -> Result<Verification> {
+    let case = cube_for_program(program)?;
+    verify(&case, alg, Cube::is_solved)
+        .ok_or_else(|| anyhow!("'{}' does not solve the pictured case", alg))
+}
+
+fn verify(case: &Cube, alg: &Algorithm, is_done: impl Fn(&Cube) -> bool) -> Option<Verification> {
+    let moves = alg.expand();
+
+    for pre in AUF_ORDER.iter() {
+        let mut cube = case.clone();
+        cube.apply_all(Move::auf(*pre).iter());
+        cube.apply_all(&moves);
+
+        for post in AUF_ORDER.iter() {
+            let mut cube = cube.clone();
+            cube.apply_all(Move::auf(*post).iter());
+            if is_done(&cube) {
+                return Some(Verification {
+                    pre_auf: Move::auf(*pre),
+                    post_auf: Move::auf(*post),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algspec::parse_algorithm;
+    use crate::ollcases;
+    use crate::pllcases;
+    use crate::pllspec::parse_program;
+
+    #[test]
+    fn every_catalog_algorithm_solves_its_case() {
+        for case in ollcases::all_cases() {
+            for alg in case.algorithms {
+                let alg = parse_algorithm(alg).unwrap();
+                assert!(
+                    verify_oll(&case.desc, &alg).is_ok(),
+                    "OLL {}: {}",
+                    case.number,
+                    alg
+                );
+            }
+        }
+        for case in pllcases::all_cases() {
+            for alg in case.algorithms {
+                let alg = parse_algorithm(alg).unwrap();
+                assert!(
+                    verify_pll(&case.program, &alg).is_ok(),
+                    "{}: {}",
+                    case.name,
+                    alg
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn aufs_agree_with_identify() {
+        let program = parse_program("1<>3 2<>8").unwrap();
+        let found = pllcases::identify(&program).unwrap().unwrap();
+        let alg = parse_algorithm(found.case.algorithms[0]).unwrap();
+        let verification = verify_pll(&program, &alg).unwrap();
+        assert_eq!(verification.pre_auf, found.pre_auf);
+        assert_eq!(verification.post_auf, found.post_auf);
+        assert_eq!(verification.to_string(), "pre-AUF U, post-AUF U'");
+    }
+
+    #[test]
+    fn oll_aufs() {
+        let sune = parse_algorithm("R U R' U R U2 R'").unwrap();
+        let desc = ollcases::case_by_number(27).unwrap().desc.clone();
+        assert_eq!(verify_oll(&desc, &sune).unwrap(), Verification::default());
+        assert_eq!(verify_oll(&desc, &sune).unwrap().to_string(), "no AUF");
+
+        let turned = crate::ollspec::rotate_desc(&desc, 1);
+        let verification = verify_oll(&turned, &sune).unwrap();
+        assert_eq!(verification.pre_auf, Move::auf(3));
+        assert_eq!(verification.post_auf, None);
+    }
+
+    #[test]
+    fn wrong_algorithms_are_rejected() {
+        let program = parse_program("3<>9 4<>6").unwrap();
+        let alg = parse_algorithm("R U R' U'").unwrap();
+        assert_eq!(
+            verify_pll(&program, &alg).unwrap_err().to_string(),
+            "'R U R' U'' does not solve the pictured case"
+        );
+
+        let desc = ollcases::case_by_number(27).unwrap().desc.clone();
+        let antisune = parse_algorithm("R U2 R' U' R U' R'").unwrap();
+        assert_eq!(
+            verify_oll(&desc, &antisune).unwrap_err().to_string(),
+            "'R U2 R' U' R U' R'' does not orient the pictured case"
+        );
+    }
+}